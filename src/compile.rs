@@ -0,0 +1,94 @@
+use crate::*;
+
+#[derive(Debug, Clone)]
+pub enum Callee { Native(NativFunction), Fn(Function) }
+#[derive(Debug, Clone)]
+#[allow(clippy::type_complexity)]
+pub enum Instr {
+    None, Value(Value), Closure(Rc<Node>),
+    Word(String, Position),
+    Element(usize, Position), Vector(usize),          // element index, element position / vector length
+    ParamType(Position, Position), Params(Vec<(String, bool)>), // type position, params position / params
+    Entry(Position), Object(Vec<(String, Position)>), // value position / keys and value positions
+    Push, Pop,
+    Stmt(usize),                                      // jump to the end of the body if the statement returned
+    Arg(Position),
+    CallWord { id: String, word_pos: Position, pos: Position, poses: Vec<Position>, cache: RefCell<Option<(usize, Vec<Type>, Rc<Callee>)>> },
+    CallValue { head_pos: Position, pos: Position, poses: Vec<Position> },
+}
+#[derive(Debug, Clone)]
+pub struct Chunk { pub code: Vec<Instr> }
+impl Default for Chunk { fn default() -> Self { Self::new() } }
+impl Chunk {
+    pub fn new() -> Self { Self { code: vec![] } }
+    pub fn emit(&mut self, instr: Instr) -> usize {
+        self.code.push(instr);
+        self.code.len() - 1
+    }
+}
+
+pub fn compile(node: &Node) -> Chunk {
+    let mut chunk = Chunk::new();
+    compile_node(node, &mut chunk);
+    chunk
+}
+pub fn compile_node(node: &Node, chunk: &mut Chunk) {
+    match node {
+        Node::None { pos:_ } => { chunk.emit(Instr::Value(Value::None)); }
+        Node::Int { v, pos:_ } => { chunk.emit(Instr::Value(Value::Int(*v))); }
+        Node::Float { v, pos:_ } => { chunk.emit(Instr::Value(Value::Float(*v))); }
+        Node::Char { v, pos:_ } => { chunk.emit(Instr::Value(Value::Char(*v))); }
+        Node::Bool { v, pos:_ } => { chunk.emit(Instr::Value(Value::Bool(*v))); }
+        Node::String { v, pos:_ } => { chunk.emit(Instr::Value(Value::String(v.clone()))); }
+        Node::Type { v, pos:_ } => { chunk.emit(Instr::Value(Value::Type(v.clone()))); }
+        Node::Key { v, pos:_ } => { chunk.emit(Instr::Value(Value::Key(v.clone()))); }
+        Node::Word { v, pos } => { chunk.emit(Instr::Word(v.clone(), pos.clone())); }
+        Node::Closure { node, pos:_ } => { chunk.emit(Instr::Closure(node.clone())); }
+        Node::Vector { nodes, pos:_ } => {
+            for (i, n) in nodes.iter().enumerate() {
+                compile_node(n, chunk);
+                chunk.emit(Instr::Element(i, n.pos().clone()));
+            }
+            chunk.emit(Instr::Vector(nodes.len()));
+        }
+        Node::Params { params, pos } => {
+            for (_, type_node, _) in params.iter() {
+                compile_node(type_node, chunk);
+                chunk.emit(Instr::ParamType(type_node.pos().clone(), pos.clone()));
+            }
+            chunk.emit(Instr::Params(params.iter().map(|(param, _, more)| (param.clone(), *more)).collect()));
+        }
+        Node::Object { entries, pos:_ } => {
+            for (_, value_node) in entries.iter() {
+                compile_node(value_node, chunk);
+                chunk.emit(Instr::Entry(value_node.pos().clone()));
+            }
+            chunk.emit(Instr::Object(entries.iter().map(|(key, value_node)| (key.clone(), value_node.pos().clone())).collect()));
+        }
+        Node::Body { nodes, pos:_ } => {
+            chunk.emit(Instr::Push);
+            let mut stmts: Vec<usize> = vec![];
+            for node in nodes.iter() {
+                compile_node(node, chunk);
+                stmts.push(chunk.emit(Instr::Stmt(0)));
+            }
+            chunk.emit(Instr::Pop);
+            let end = chunk.emit(Instr::None) + 1;
+            for stmt in stmts { chunk.code[stmt] = Instr::Stmt(end); }
+        }
+        Node::Node { head, args, pos } => {
+            chunk.emit(Instr::Push);
+            for arg in args.iter() {
+                compile_node(arg, chunk);
+                chunk.emit(Instr::Arg(arg.pos().clone()));
+            }
+            let poses: Vec<Position> = args.iter().map(|arg| arg.pos().clone()).collect();
+            if let Node::Word { v, pos: word_pos } = head.as_ref() {
+                chunk.emit(Instr::CallWord { id: v.clone(), word_pos: word_pos.clone(), pos: pos.clone(), poses, cache: RefCell::new(None) });
+            } else {
+                compile_node(head, chunk);
+                chunk.emit(Instr::CallValue { head_pos: head.pos().clone(), pos: pos.clone(), poses });
+            }
+        }
+    }
+}
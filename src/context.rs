@@ -5,20 +5,38 @@ pub struct Scope {
     pub vars: HashMap<String, (Value, bool, Position)>,
     pub funcs: HashMap<String, Vec<(Function, Position)>>,
     pub native_funcs: HashMap<String, Vec<(NativFunction, Position)>>,
-    pub subs: HashMap<String, Scope>
+    pub subs: HashMap<String, Scope>,
+    pub cells: HashMap<String, Rc<RefCell<Value>>> // the captured variables, shared by every closure that refers to them
 }
+impl Default for Scope { fn default() -> Self { Self::new() } }
 impl Scope {
     pub fn new() -> Self {
         Scope {
             vars: HashMap::new(),
             funcs: HashMap::new(),
             native_funcs: HashMap::new(),
-            subs: HashMap::new()
+            subs: HashMap::new(),
+            cells: HashMap::new()
+        }
+    }
+    // the cells follow the variables they capture until the scope ends
+    pub fn sync_cells(&self) {
+        for (id, cell) in self.cells.iter() {
+            if let Some((value, _, _)) = self.vars.get(id) { *cell.borrow_mut() = value.clone(); }
+        }
+    }
+    pub fn load_cells(&mut self) {
+        for (id, cell) in self.cells.iter() {
+            if let Some((value, _, _)) = self.vars.get_mut(id) { *value = cell.borrow().clone(); }
         }
     }
     // create
     pub fn create_var(&mut self, id: String, value: Value, mutable: bool, pos: Position, overwrite: bool) -> Result<(), Error> {
-        if self.vars.contains_key(&id) && !overwrite { return Err(Error::AlreadyDefined(id)) }
+        if self.vars.contains_key(&id) && !overwrite { return Err(Error::AlreadyDefined(id, pos)) }
+        // a new variable of the same name isn't the one captured before
+        if let Some(cell) = self.cells.remove(&id) {
+            if let Some((old, _, _)) = self.vars.get(&id) { *cell.borrow_mut() = old.clone(); }
+        }
         self.vars.insert(id, (value, mutable, pos));
         Ok(())
     }
@@ -34,7 +52,7 @@ impl Scope {
                     Ok(())
                 }
             }
-            Some(_) => Err(Error::AlreadyDefined(id))
+            Some(_) => Err(Error::AlreadyDefined(id, pos))
         }
     }
     pub fn create_native_fn(&mut self, id: String, func: NativFunction, pos: Position) -> Result<(), Error> {
@@ -49,7 +67,7 @@ impl Scope {
                     Ok(())
                 }
             }
-            Some(_) => Err(Error::AlreadyDefined(id))
+            Some(_) => Err(Error::AlreadyDefined(id, pos))
         }
     }
 
@@ -71,10 +89,7 @@ impl Scope {
         }
     }
     pub fn is_mutable(&self, id: &String) -> Option<bool> {
-        match self.vars.get(id) {
-            Some((_, mutable, _)) => Some(*mutable),
-            None => None
-        }
+        self.vars.get(id).map(|(_, mutable, _)| *mutable)
     }
     pub fn get_var_mut(&mut self, id: &String) -> Option<&mut Value> {
         match self.vars.get_mut(id) {
@@ -84,28 +99,22 @@ impl Scope {
     }
     // get fn
     pub fn get_fn(&self, id: &String, pattern: &Vec<Type>) -> Option<&Function> {
-        match self.funcs.get(id) {
-            Some(defs) => {
-                for (func, _) in defs.iter() {
-                    if func.pattern_match(pattern) { return Some(func) }
-                }
-                None
-            }
-            None => None
+        let mut best: Option<(&Function, (usize, usize))> = None;
+        for (func, _) in self.funcs.get(id)?.iter() { // the most specific match, the first one of equals
+            if !func.pattern_match(pattern) { continue }
+            let rank = params_rank(&func.params, pattern);
+            if best.as_ref().is_none_or(|(_, best)| rank > *best) { best = Some((func, rank)) }
         }
+        best.map(|(func, _)| func)
     }
     pub fn get_fn_mut(&mut self, id: &String, pattern: &Vec<Type>) -> Option<&mut Function> {
-        match self.funcs.get_mut(id) {
-            Some(defs) => {
-                for (func, _) in defs.iter_mut() {
-                    if func.pattern_match(pattern) {
-                        return Some(func)
-                    }
-                }
-                None
-            }
-            None => None
+        let mut best: Option<(&mut Function, (usize, usize))> = None;
+        for (func, _) in self.funcs.get_mut(id)?.iter_mut() { // the most specific match, the first one of equals
+            if !func.pattern_match(pattern) { continue }
+            let rank = params_rank(&func.params, pattern);
+            if best.as_ref().is_none_or(|(_, best)| rank > *best) { best = Some((func, rank)) }
         }
+        best.map(|(func, _)| func)
     }
     pub fn get_fn_pos(&self, id: &String, pattern: &Vec<Type>) -> Option<&Position> {
         match self.funcs.get(id) {
@@ -175,28 +184,22 @@ impl Scope {
     }
     // get native fn
     pub fn get_native_fn(&self, id: &String, pattern: &Vec<Type>) -> Option<&NativFunction> {
-        match self.native_funcs.get(id) {
-            Some(defs) => {
-                for (func, _) in defs.iter() {
-                    if func.pattern_match(pattern) { return Some(func) }
-                }
-                None
-            }
-            None => None
+        let mut best: Option<(&NativFunction, (usize, usize))> = None;
+        for (func, _) in self.native_funcs.get(id)?.iter() { // the most specific match, the first one of equals
+            if !func.pattern_match(pattern) { continue }
+            let rank = params_rank(&func.params, pattern);
+            if best.as_ref().is_none_or(|(_, best)| rank > *best) { best = Some((func, rank)) }
         }
+        best.map(|(func, _)| func)
     }
     pub fn get_native_fn_mut(&mut self, id: &String, pattern: &Vec<Type>) -> Option<&mut NativFunction> {
-        match self.native_funcs.get_mut(id) {
-            Some(defs) => {
-                for (func, _) in defs.iter_mut() {
-                    if func.pattern_match(pattern) {
-                        return Some(func)
-                    }
-                }
-                None
-            }
-            None => None
+        let mut best: Option<(&mut NativFunction, (usize, usize))> = None;
+        for (func, _) in self.native_funcs.get_mut(id)?.iter_mut() { // the most specific match, the first one of equals
+            if !func.pattern_match(pattern) { continue }
+            let rank = params_rank(&func.params, pattern);
+            if best.as_ref().is_none_or(|(_, best)| rank > *best) { best = Some((func, rank)) }
         }
+        best.map(|(func, _)| func)
     }
     pub fn get_native_fn_params(&self, id: &String, params: &Params) -> Option<&NativFunction> {
         match self.native_funcs.get(id) {
@@ -266,48 +269,167 @@ impl Scope {
     }
 }
 impl PartialEq for Scope {
-    fn eq(&self, other: &Self) -> bool { false }
+    fn eq(&self, _other: &Self) -> bool { false }
 }
+// a non-inline call, with `env` the scope at `start` is a captured environment that definitions can shadow
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame { pub start: usize, pub id: usize, pub env: bool }
 #[derive(Debug, Clone)]
 pub struct Context {
     pub scopes: Vec<Scope>,
+    pub frames: Vec<Frame>, // every non-inline call
+    pub frame_count: usize, // for the ids of new frames, the top level is frame 0
     pub global: Scope,
+    pub version: usize, // changes whenever a global function is defined
     pub trace: Vec<Position>,
+    pub sources: HashMap<String, String>, // text of every scanned file for the diagnostics
     pub path: String,
     pub std_path: Option<String>,
+    pub vm: Option<Vm>, // executes closures as bytecode instead of walking the nodes
+    pub loops: Vec<Option<String>>, // labels of the running loops
+    pub label: Option<String>, // the loop a labeled break or continue is for
+    pub resume: Vec<Resume>, // where the running generator continues, outermost last
+    pub generator: Option<usize>, // the frame of the running generator, the only one a yield can be in
+    pub prelude: Scope, // the global scope after the std, what every module starts with
+    pub exports: Vec<(String, Position)>, // the names the running module exports
+    pub modules: HashMap<String, Scope>, // the loaded modules by the canonical path of their file
+    pub imported: HashSet<String>, // the files already imported into the global scope
+    pub importing: Vec<(String, String)>, // the files being imported by canonical and resolved path, innermost last
 }
 impl Context {
     pub fn new(path: String, std_path: Option<String>) -> Self {
-        Self { scopes: vec![Scope::new()], global: Scope::new(), trace: vec![], path, std_path }
+        Self { scopes: vec![Scope::new()], frames: vec![], frame_count: 0, global: Scope::new(), version: 0, trace: vec![], sources: HashMap::new(), path, std_path, vm: None, loops: vec![], label: None, resume: vec![], generator: None, prelude: Scope::new(), exports: vec![], modules: HashMap::new(), imported: HashSet::new(), importing: vec![] }
+    }
+    // calls share the global scope and the scope stack, a non-inline call only sees the scopes of its own frame
+    pub fn call(&mut self, inline: bool) {
+        if !inline {
+            self.frame_count += 1;
+            self.frames.push(Frame { start: self.scopes.len(), id: self.frame_count, env: false });
+            self.scopes.push(Scope::new());
+        }
+    }
+    pub fn after_call(&mut self, inline: bool) {
+        if !inline {
+            if let Some(frame) = self.frames.pop() {
+                for scope in self.scopes[frame.start..].iter() { scope.sync_cells(); }
+                self.scopes.truncate(frame.start);
+            }
+        }
     }
-    pub fn call(path: String, context: &Context, inline: bool) -> Self {
-        Self {
-            scopes: if inline { context.scopes.clone() } else { vec![Scope::new()] },
-            global: context.global.clone(),
-            trace: context.trace.clone(),
-            path: context.path.clone(), std_path: context.std_path.clone()
+    // a call on a captured environment, its variables are cells shared with the frame that created it
+    // and every other closure of that frame, the running scopes are synced with the cells around the call
+    pub fn call_env(&mut self, env: &Env) {
+        for scope in self.scopes.iter() { scope.sync_cells(); }
+        let mut scope = env.scope.borrow().clone();
+        scope.load_cells();
+        self.frame_count += 1;
+        self.frames.push(Frame { start: self.scopes.len(), id: self.frame_count, env: true });
+        self.scopes.push(scope);
+        self.scopes.push(Scope::new());
+    }
+    pub fn after_call_env(&mut self, env: &Env) {
+        if let Some(frame) = self.frames.pop() {
+            for scope in self.scopes[frame.start..].iter() { scope.sync_cells(); }
+            let scope = std::mem::take(&mut self.scopes[frame.start]);
+            self.scopes.truncate(frame.start);
+            for scope in self.scopes.iter_mut() { scope.load_cells(); }
+            *env.scope.borrow_mut() = scope;
+        }
+    }
+    // an inline call on an environment, only the module functions have one, still sees the scopes of its caller
+    pub fn call_inline_env(&mut self, env: &Env) {
+        for scope in self.scopes.iter() { scope.sync_cells(); }
+        let mut scope = env.scope.borrow().clone();
+        scope.load_cells();
+        self.scopes.push(scope);
+        self.scopes.push(Scope::new());
+    }
+    pub fn after_call_inline_env(&mut self, env: &Env, start: usize) {
+        for scope in self.scopes[start..].iter() { scope.sync_cells(); }
+        let scope = std::mem::take(&mut self.scopes[start]);
+        self.scopes.truncate(start);
+        for scope in self.scopes.iter_mut() { scope.load_cells(); }
+        *env.scope.borrow_mut() = scope;
+    }
+    // a resumed generator hasn't reached the yield it stopped at while the interpreter still has places to continue at
+    pub fn resuming(&self) -> bool { !self.resume.is_empty() }
+    pub fn frame_id(&self) -> usize { self.frames.last().map(|frame| frame.id).unwrap_or(0) }
+    pub fn frame(&self) -> &[Scope] {
+        let start = self.frames.last().map(|frame| frame.start).unwrap_or(0);
+        &self.scopes[start..]
+    }
+    pub fn frame_mut(&mut self) -> &mut [Scope] {
+        let start = self.frames.last().map(|frame| frame.start).unwrap_or(0);
+        &mut self.scopes[start..]
+    }
+    // the scopes of the frame without its captured environment
+    pub fn frame_locals(&self) -> &[Scope] {
+        match self.frames.last() {
+            Some(frame) => &self.scopes[frame.start + frame.env as usize..],
+            None => &self.scopes
+        }
+    }
+    // the local variables and functions `node` refers to, nothing if it doesn't refer to any
+    pub fn capture(&mut self, node: &Node) -> Option<Env> {
+        let mut names: Vec<&String> = vec![];
+        node.names(&mut names);
+        let mut env = Scope::new();
+        let frame_id = self.frame_id();
+        for name in names {
+            let name = name.split('.').next().unwrap_or(name); // `geom.area` refers to `geom`
+            for scope in self.frame_mut().iter_mut().rev() {
+                if let Some(var) = scope.vars.get(name) {
+                    if !env.vars.contains_key(name) {
+                        let cell = scope.cells.entry(name.to_string()).or_insert_with(|| Rc::new(RefCell::new(var.0.clone())));
+                        *cell.borrow_mut() = var.0.clone();
+                        env.cells.insert(name.to_string(), cell.clone());
+                        env.vars.insert(name.to_string(), var.clone());
+                    }
+                }
+                if let Some(defs) = scope.funcs.get(name) {
+                    env.funcs.entry(name.to_string()).or_default().extend(defs.iter().cloned());
+                }
+            }
         }
+        if env.vars.is_empty() && env.funcs.is_empty() { return None }
+        Some(Env::new(env, frame_id))
     }
-    pub fn after_call(&mut self, context: Context, inline: bool) {
-        if inline { self.scopes = context.scopes; } // copy scopes if inline
-        self.global = context.global;
-        self.trace = context.trace;
+    // only defined in the captured environment, where a new definition shadows it
+    pub fn is_captured(&self, id: &String) -> bool {
+        match self.frames.last() {
+            Some(frame) if frame.env => self.global.get_var(id).is_none()
+                && self.frame_locals().iter().all(|scope| scope.get_var(id).is_none())
+                && self.scopes[frame.start].get_var(id).is_some(),
+            _ => false
+        }
     }
     pub fn push(&mut self) { self.scopes.push(Scope::new()) }
-    pub fn pop(&mut self) -> Option<Scope> { self.scopes.pop() }
+    pub fn pop(&mut self) -> Option<Scope> {
+        let scope = self.scopes.pop();
+        if let Some(scope) = &scope { scope.sync_cells(); }
+        scope
+    }
     pub fn trace_push(&mut self, pos: &Position) { self.trace.push(pos.clone()); }
     pub fn trace_pop(&mut self) -> Option<Position> { self.trace.pop() }
+    pub fn print_error(&self, e: &Error) -> String { print_error(e, &self.trace, &self.sources, use_color()) }
+    // lengths of the scope, frame and trace stacks to roll back to after an error
+    pub fn checkpoint(&self) -> (usize, usize, usize) { (self.scopes.len(), self.frames.len(), self.trace.len()) }
+    pub fn restore(&mut self, (scopes, frames, trace): (usize, usize, usize)) {
+        self.scopes.truncate(scopes);
+        self.frames.truncate(frames);
+        self.trace.truncate(trace);
+    }
     // scope of var
     pub fn get_scope_var(&self, id: &String) -> Option<&Scope> {
         if self.global.get_var(id).is_some() { return Some(&self.global) }
-        for scope in self.scopes.iter().rev() {
+        for scope in self.frame().iter().rev() {
             if scope.get_var(id).is_some() { return Some(scope) }
         }
         None
     }
     pub fn get_scope_var_mut(&mut self, id: &String) -> Option<&mut Scope> {
         if self.global.get_var(id).is_some() { return Some(&mut self.global) }
-        for scope in self.scopes.iter_mut().rev() {
+        for scope in self.frame_mut().iter_mut().rev() {
             if scope.get_var(id).is_some() { return Some(scope) }
         }
         None
@@ -315,14 +437,14 @@ impl Context {
     // scope of fn
     pub fn get_scope_fn(&self, id: &String, pattern: &Vec<Type>) -> Option<&Scope> {
         if self.global.get_fn(id, pattern).is_some() { return Some(&self.global) }
-        for scope in self.scopes.iter().rev() {
+        for scope in self.frame().iter().rev() {
             if scope.get_fn(id, pattern).is_some() { return Some(scope) }
         }
         None
     }
     pub fn get_scope_fn_mut(&mut self, id: &String, pattern: &Vec<Type>) -> Option<&mut Scope> {
         if self.global.get_fn_mut(id, pattern).is_some() { return Some(&mut self.global) }
-        for scope in self.scopes.iter_mut().rev() {
+        for scope in self.frame_mut().iter_mut().rev() {
             if scope.get_fn_mut(id, pattern).is_some() { return Some(scope) }
         }
         None
@@ -330,14 +452,14 @@ impl Context {
     // scope of fn
     pub fn get_scope_fn_any(&self, id: &String) -> Option<&Scope> {
         if self.global.get_fn_any(id).is_some() { return Some(&self.global) }
-        for scope in self.scopes.iter().rev() {
+        for scope in self.frame().iter().rev() {
             if scope.get_fn_any(id).is_some() { return Some(scope) }
         }
         None
     }
     pub fn get_scope_fn_any_mut(&mut self, id: &String) -> Option<&mut Scope> {
         if self.global.get_fn_any_mut(id).is_some() { return Some(&mut self.global) }
-        for scope in self.scopes.iter_mut().rev() {
+        for scope in self.frame_mut().iter_mut().rev() {
             if scope.get_fn_any_mut(id).is_some() { return Some(scope) }
         }
         None
@@ -345,14 +467,14 @@ impl Context {
     // scope of fn params
     pub fn get_scope_fn_params(&self, id: &String, params: &Params) -> Option<&Scope> {
         if self.global.get_fn_params(id, params).is_some() { return Some(&self.global) }
-        for scope in self.scopes.iter().rev() {
+        for scope in self.frame().iter().rev() {
             if scope.get_fn_params(id, params).is_some() { return Some(scope) }
         }
         None
     }
     pub fn get_scope_fn_params_mut(&mut self, id: &String, params: &Params) -> Option<&mut Scope> {
         if self.global.get_fn_params_mut(id, params).is_some() { return Some(&mut self.global) }
-        for scope in self.scopes.iter_mut().rev() {
+        for scope in self.frame_mut().iter_mut().rev() {
             if scope.get_fn_params_mut(id, params).is_some() { return Some(scope) }
         }
         None
@@ -360,14 +482,14 @@ impl Context {
     // scope of native fn
     pub fn get_scope_native_fn(&self, id: &String, pattern: &Vec<Type>) -> Option<&Scope> {
         if self.global.get_native_fn(id, pattern).is_some() { return Some(&self.global) }
-        for scope in self.scopes.iter().rev() {
+        for scope in self.frame().iter().rev() {
             if scope.get_native_fn(id, pattern).is_some() { return Some(scope) }
         }
         None
     }
     pub fn get_scope_native_fn_mut(&mut self, id: &String, pattern: &Vec<Type>) -> Option<&mut Scope> {
         if self.global.get_native_fn_mut(id, pattern).is_some() { return Some(&mut self.global) }
-        for scope in self.scopes.iter_mut().rev() {
+        for scope in self.frame_mut().iter_mut().rev() {
             if scope.get_native_fn_mut(id, pattern).is_some() { return Some(scope) }
         }
         None
@@ -375,14 +497,14 @@ impl Context {
     // scope of fn
     pub fn get_scope_native_fn_any(&self, id: &String) -> Option<&Scope> {
         if self.global.get_native_fn_any(id).is_some() { return Some(&self.global) }
-        for scope in self.scopes.iter().rev() {
+        for scope in self.frame().iter().rev() {
             if scope.get_native_fn_any(id).is_some() { return Some(scope) }
         }
         None
     }
     pub fn get_scope_native_fn_any_mut(&mut self, id: &String) -> Option<&mut Scope> {
         if self.global.get_native_fn_any_mut(id).is_some() { return Some(&mut self.global) }
-        for scope in self.scopes.iter_mut().rev() {
+        for scope in self.frame_mut().iter_mut().rev() {
             if scope.get_native_fn_any_mut(id).is_some() { return Some(scope) }
         }
         None
@@ -390,32 +512,39 @@ impl Context {
 
     // create
     pub fn create_var(&mut self, id: String, value: Value, mutable: bool, pos: Position, overwrite: bool) -> Result<(), Error> {
+        if self.is_captured(&id) { return self.scopes.last_mut().unwrap().create_var(id, value, mutable, pos, overwrite) }
         match self.get_scope_var_mut(&id) {
             None => self.scopes.last_mut().unwrap().create_var(id, value, mutable, pos, overwrite),
-            Some(_) => if overwrite { self.scopes.last_mut().unwrap().create_var(id, value, mutable, pos, overwrite) } else { Err(Error::AlreadyDefined(id)) }
+            Some(_) => if overwrite { self.scopes.last_mut().unwrap().create_var(id, value, mutable, pos, overwrite) } else { Err(Error::AlreadyDefined(id, pos)) }
         }
     }
     pub fn create_fn(&mut self, id: String, func: Function, pos: Position) -> Result<(), Error> {
+        let captured = match self.frames.last() {
+            Some(frame) if frame.env => self.global.get_fn_params(&id, &func.params).is_none()
+                && self.frame_locals().iter().all(|scope| scope.get_fn_params(&id, &func.params).is_none()),
+            _ => false
+        };
         match self.get_scope_fn_params_mut(&id, &func.params) {
-            None => self.scopes.last_mut().unwrap().create_fn(id, func, pos),
-            Some(scope) => scope.create_fn(id, func, pos)
+            Some(scope) if !captured => scope.create_fn(id, func, pos),
+            _ => self.scopes.last_mut().unwrap().create_fn(id, func, pos)
         }
     }
     pub fn create_fn_global(&mut self, id: String, func: Function, pos: Position) -> Result<(), Error> {
+        self.version += 1;
         self.global.create_fn(id, func, pos)
     }
     pub fn create_native_fn(&mut self, id: String, func: NativFunction, pos: Position) -> Result<(), Error> {
+        self.version += 1;
         self.global.create_native_fn(id, func, pos)
     }
-    pub fn create_params(&mut self, params: &Params, values: Vec<Value>, poses: Vec<Position>, inline: bool) -> Result<(), Error> {
+    pub fn create_params(&mut self, params: &Params, values: Vec<Value>, poses: &[Position], inline: bool) -> Result<(), Error> {
         let mut value_idx: usize = 0; // in case of a param that accepts more values we need two iterator variables
-        for i in 0..params.len() {
-            let (param, param_type, more) = &params[i];
+        for (param, param_type, more) in params.iter() {
             if *more { // more than one accepted
                 let mut vec_values: Vec<Value> = vec![]; // arg storage
                 let mut pos = poses[value_idx].clone(); // initiate position
                 while let Some(value) = values.get(value_idx) {
-                    if &value.typ() != param_type { break } // different type stopping the collection
+                    if !value.typ().matches(param_type) { break } // different type stopping the collection
                     pos = Position::between(pos, poses[value_idx].clone()); // update position
                     vec_values.push(values[value_idx].clone());
                     value_idx += 1; // update values_idx
@@ -431,57 +560,110 @@ impl Context {
     }
     
     pub fn del_var(&mut self, id: &String) -> Option<(Value, bool, Position)> {
-        self.get_scope_var_mut(&id)?.del_var(id)
+        self.get_scope_var_mut(id)?.del_var(id)
     }
     
-    pub fn change(&mut self, id: String, value: Value) -> Result<(), Error> {
-        match self.global.vars.get_mut(&id) { // first look in the global scope
-            Some((old_value, mutable, pos)) => if *mutable {
+    pub fn change(&mut self, id: String, value: Value, pos: Position) -> Result<(), Error> {
+        if let Some((old_value, mutable, _)) = self.global.vars.get_mut(&id) { // first look in the global scope
+            if !*mutable { return Err(Error::Immutable(id, pos)) }
+            *old_value = value;
+            return Ok(())
+        }
+        for scope in self.frame_mut().iter_mut().rev() { // than look in the scope stack in reverse
+            if let Some((old_value, mutable, _)) = scope.vars.get_mut(&id) {
+                if !*mutable { return Err(Error::Immutable(id, pos)) }
                 *old_value = value;
                 return Ok(())
-            } else {
-                return Err(Error::Immutable(id))
             }
-            None => {}
         }
-        for scope in self.scopes.iter_mut().rev() { // than look in the scope stack in reverse
-            match scope.vars.get_mut(&id) {
-                Some((old_value, mutable, pos)) => if *mutable {
+        // `o.x` is the member `x` of the object `o`, as mutable as `o`
+        if let Some((path, member)) = id.rsplit_once('.') {
+            if let Some(mutable) = self.is_mutable(&id) {
+                if !mutable { return Err(Error::Immutable(id, pos)) }
+                if let Some(old_value) = self.get_object_mut(path).and_then(|scope| scope.get_var_mut(&member.to_string())) {
+                    if value.typ() != old_value.typ() { return Err(Error::ExpectedType(old_value.typ(), value.typ(), pos)) }
                     *old_value = value;
                     return Ok(())
-                } else {
-                    return Err(Error::Immutable(id))
                 }
-                None => {}
             }
         }
-        Err(Error::NotDefined(id))
+        Err(Error::NotDefined(id, pos))
     }
     // get var
     pub fn get_var(&self, id: &String) -> Option<&Value> {
         match self.get_scope_var(id) {
             Some(scope) => scope.get_var(id),
-            None => self.global.get_var(id)
+            None => match id.rsplit_once('.') {
+                Some((path, member)) => self.get_object(path)?.get_var(&member.to_string()),
+                None => None
+            }
         }
     }
+    // a union or vector of declared types, like `Point|none`, which the scanner can't know yet
+    pub fn get_type(&self, word: &str) -> Option<Type> {
+        if !word.contains(['|', '<']) { return None }
+        parse_type(word, &|name| match self.get_var(&name.to_string()) {
+            Some(Value::Type(typ)) => Some(typ.clone()),
+            _ => None
+        })
+    }
+    // the object a dotted path like `geom.shapes` leads to
+    pub fn get_object(&self, path: &str) -> Option<&Scope> {
+        let mut ids = path.split('.');
+        let Some(Value::Object(first)) = self.get_var(&ids.next()?.to_string()) else { return None };
+        let mut scope = first;
+        for id in ids {
+            let Some(Value::Object(member)) = scope.get_var(&id.to_string()) else { return None };
+            scope = member;
+        }
+        Some(scope)
+    }
+    pub fn get_object_mut(&mut self, path: &str) -> Option<&mut Scope> {
+        let mut ids = path.split('.');
+        let Some(Value::Object(first)) = self.get_var_mut(&ids.next()?.to_string()) else { return None };
+        let mut scope = first;
+        for id in ids {
+            let Some(Value::Object(member)) = scope.get_var_mut(&id.to_string()) else { return None };
+            scope = member;
+        }
+        Some(scope)
+    }
     pub fn get_var_pos(&self, id: &String) -> Option<&Position> {
         match self.get_scope_var(id) {
             Some(scope) => scope.get_var_pos(id),
             None => self.global.get_var_pos(id)
         }
     }
+    // arguments of a native call, a mismatch is a type error at the argument instead of a panic
+    pub fn arg<T: FromValue>(&self, id: &str) -> Result<T, Error> {
+        let pos = self.arg_pos(id);
+        match self.get_var(&id.to_string()) {
+            Some(value) => {
+                let typ = value.typ();
+                T::from_value(value.clone()).ok_or_else(|| Error::ExpectedType(T::typ(), typ, pos))
+            }
+            None => Err(Error::NotDefined(id.to_string(), pos))
+        }
+    }
+    pub fn arg_pos(&self, id: &str) -> Position {
+        match self.get_var_pos(&id.to_string()) {
+            Some(pos) => pos.clone(),
+            None => Position::new(0..0, 0..0, &self.path)
+        }
+    }
     pub fn is_mutable(&self, id: &String) -> Option<bool> {
         match self.get_scope_var(id) {
             Some(scope) => scope.is_mutable(id),
-            None => self.global.is_mutable(id)
+            None => match id.split_once('.') {
+                Some((root, _)) if self.get_var(id).is_some() => self.is_mutable(&root.to_string()),
+                _ => None
+            }
         }
     }
     pub fn get_var_mut(&mut self, id: &String) -> Option<&mut Value> {
-        for scope in self.scopes.iter_mut() {
-            match scope.get_var_mut(id) {
-                Some(value) => return Some(value),
-                None => {}
-            }
+        let start = self.frames.last().map(|frame| frame.start).unwrap_or(0);
+        for scope in self.scopes[start..].iter_mut().rev() {
+            if let Some(value) = scope.get_var_mut(id) { return Some(value) }
         }
         match self.global.get_var_mut(id) {
             Some(value) => Some(value),
@@ -503,7 +685,7 @@ impl Context {
     }
     pub fn fn_exists(&self, id: &String) -> bool {
         if self.global.fn_exists(id) { return true }
-        for scope in self.scopes.iter().rev() {
+        for scope in self.frame().iter().rev() {
             if scope.fn_exists(id) { return true }
         }
         false
@@ -517,11 +699,60 @@ impl Context {
     }
     pub fn native_fn_exists(&self, id: &String) -> bool {
         if self.global.native_fn_exists(id) { return true }
-        for scope in self.scopes.iter().rev() {
+        for scope in self.frame().iter().rev() {
             if scope.native_fn_exists(id) { return true }
         }
         false
     }
+    // the most specific of all visible definitions matching the pattern, natives and functions alike
+    // an inner definition with the same params shadows the outer ones, other equal matches are ambiguous
+    pub fn resolve_fn(&self, id: &String, pattern: &Vec<Type>, pos: &Position) -> Result<Option<Callee>, Error> {
+        enum Found<'a> { Native(&'a NativFunction), Fn(&'a Function) }
+        let mut best: Option<(&Params, (usize, usize), Found)> = None;
+        let mut ambiguous: Vec<&Params> = vec![];
+        // innermost first and user definitions before natives, the first of the same params shadows the others
+        let scopes = self.frame().iter().rev().chain(std::iter::once(&self.global));
+        let candidates = scopes.flat_map(|scope| {
+            let funcs = scope.funcs.get(id).into_iter().flatten().map(|(func, _)| (&func.params, Found::Fn(func)));
+            let natives = scope.native_funcs.get(id).into_iter().flatten().map(|(func, _)| (&func.params, Found::Native(func)));
+            funcs.chain(natives)
+        });
+        for (params, found) in candidates {
+            let matches = match &found {
+                Found::Native(func) => func.pattern_match(pattern),
+                Found::Fn(func) => func.pattern_match(pattern)
+            };
+            if !matches { continue }
+            let rank = params_rank(params, pattern);
+            match &best {
+                Some((best_params, best_rank, _)) if rank == *best_rank => if !params_exact(params, best_params) { ambiguous.push(params) }
+                Some((_, best_rank, _)) if rank < *best_rank => {}
+                _ => {
+                    best = Some((params, rank, found));
+                    ambiguous.clear();
+                }
+            }
+        }
+        let Some((params, _, found)) = best else { return Ok(self.resolve_member_fn(id, pattern)) };
+        if !ambiguous.is_empty() {
+            let patterns = std::iter::once(params).chain(ambiguous).map(|params| params.iter().map(|(_, typ, more)| (typ.clone(), *more)).collect()).collect();
+            return Err(Error::AmbiguousCall(id.clone(), pattern.clone(), patterns, pos.clone()))
+        }
+        Ok(Some(match found {
+            Found::Native(func) => Callee::Native(func.clone()),
+            Found::Fn(func) => Callee::Fn(func.clone())
+        }))
+    }
+    // `geom.area` is the function `area` of the object `geom`
+    fn resolve_member_fn(&self, id: &String, pattern: &Vec<Type>) -> Option<Callee> {
+        let (path, member) = id.rsplit_once('.')?;
+        let scope = self.get_object(path)?;
+        let member = member.to_string();
+        match scope.get_native_fn(&member, pattern) {
+            Some(func) => Some(Callee::Native(func.clone())),
+            None => scope.get_fn(&member, pattern).map(|func| Callee::Fn(func.clone()))
+        }
+    }
     // get patterns
     pub fn get_patterns(&self, id: &String) -> Option<Vec<Vec<(Type, bool)>>> {
         match self.get_scope_fn_any(id) {
@@ -0,0 +1,71 @@
+use crate::*;
+
+// embedding api around a context, every error leaves the engine usable for the next call
+// a script calling `exit` ends with `Error::Exit` instead of ending the host process
+#[derive(Debug, Clone)]
+pub struct Engine { pub context: Context }
+impl Engine {
+    // native functions and the embedded std
+    pub fn new() -> Result<Self, Error> { Ok(Self::from_context(std_context_embedded("<engine>".to_string())?)) }
+    pub fn from_context(context: Context) -> Self { Self { context } }
+
+    pub fn scan(&mut self, path: &str, text: String) -> Result<Node, Error> {
+        self.context.trace.clear();
+        scan_context(&path.to_string(), text, &mut self.context)
+    }
+    pub fn run(&mut self, node: &Node) -> Result<Option<Value>, Error> {
+        self.context.trace.clear();
+        let checkpoint = self.context.checkpoint();
+        match run_node(node, &mut self.context) {
+            Ok((value, _)) => Ok(value),
+            Err(e) => {
+                self.context.restore((checkpoint.0, checkpoint.1, self.context.trace.len())); // keep the trace for printing
+                Err(e)
+            }
+        }
+    }
+    pub fn eval_str(&mut self, text: &str) -> Result<Option<Value>, Error> {
+        let node = self.scan("<eval>", text.to_string())?;
+        self.run(&node)
+    }
+    pub fn eval_file(&mut self, path: &str) -> Result<Option<Value>, Error> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => return Err(Error::TargetFileNotFound(path.to_string(), Position::new(0..0, 0..0, &path.to_string())))
+        };
+        let node = self.scan(path, text)?;
+        let prev_path = std::mem::replace(&mut self.context.path, path.to_string());
+        let res = self.run(&node);
+        self.context.path = prev_path;
+        res
+    }
+    pub fn call_function(&mut self, name: &str, args: Vec<Value>) -> Result<Option<Value>, Error> {
+        self.context.trace.clear();
+        let checkpoint = self.context.checkpoint();
+        let pos = Position::new(0..0, 0..0, &"<call>".to_string());
+        let types: Vec<Type> = args.iter().map(|value| value.typ()).collect();
+        let poses = vec![pos.clone(); args.len()];
+        self.context.push(); // popped by the call
+        match call_word(&name.to_string(), &pos, &pos, args, types, &poses, &mut self.context) {
+            Ok((value, _)) => Ok(value),
+            Err(e) => {
+                self.context.restore((checkpoint.0, checkpoint.1, self.context.trace.len()));
+                Err(e)
+            }
+        }
+    }
+    pub fn get_global(&self, name: &str) -> Option<&Value> { self.context.global.get_var(&name.to_string()) }
+    pub fn set_global(&mut self, name: &str, value: Value) -> Result<(), Error> {
+        let pos = Position::new(0..0, 0..0, &"<engine>".to_string());
+        self.context.global.create_var(name.to_string(), value, false, pos, true)
+    }
+    pub fn create_native_fn(&mut self, name: &str, func: NativFunction) -> Result<(), Error> {
+        let pos = Position::new(0..0, 0..0, &"<engine>".to_string());
+        self.context.create_native_fn(name.to_string(), func, pos)
+    }
+    // `engine.register_fn("add", |a: i64, b: i64| a + b)`, the params come from the argument types
+    pub fn register_fn<Args>(&mut self, name: &str, f: impl IntoNative<Args>) -> Result<(), Error> {
+        self.create_native_fn(name, native_fn(f))
+    }
+    pub fn print_error(&self, e: &Error) -> String { self.context.print_error(e) }
+}
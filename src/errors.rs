@@ -1,9 +1,9 @@
 use crate::*;
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct Position { pub ln: Range<usize>, pub col: Range<usize>, pub path: String }
+pub struct Position { pub ln: Range<usize>, pub col: Range<usize>, pub path: Rc<str> }
 impl Position {
-    pub fn new(ln: Range<usize>, col: Range<usize>, path: &String) -> Self { Self { ln, col, path: path.clone() } }
+    pub fn new(ln: Range<usize>, col: Range<usize>, path: &String) -> Self { Self { ln, col, path: Rc::from(path.as_str()) } }
     pub fn between(p1: Self, p2: Self) -> Self {
         Self { ln: p1.ln.start..p2.ln.end, col: p1.col.start..p2.col.end, path: p1.path.clone() }
     }
@@ -18,85 +18,209 @@ pub fn pattern_display(pattern: &Vec<(Type, bool)>) -> String {
 pub fn patterns_display(patterns: &Vec<Vec<(Type, bool)>>) -> String {
     patterns.iter().map(|pattern| format!("({})", pattern_display(pattern))).collect::<Vec<String>>().join("\n")
 }
+// process exit codes
+pub const EXIT_RUNTIME: i32 = 1;
+pub const EXIT_SCAN: i32 = 2;
+pub const EXIT_FILE_NOT_FOUND: i32 = 3;
 #[derive(Debug, Clone)]
 pub enum Error {
-    Error(String),
-    TargetFileNotFound(String), FileNotFound(String),
-    UnexpectedEnd, UnexpectedSymbol(char), ExpectedSymbol(char, char), ExpectedSymbols(Vec<char>, char), ExpectedWord,
-    ParseFloat(String), ParseInt(String), ParseIntOverflow(String), ParseIntNegOverflow(String),
-    ParseChar(String), ParseBool(String), ParseString(String),
-    UnclosedChar, UnclosedString,
-    NotDefined(String), AlreadyDefined(String), Immutable(String),
-    NotDefinedPath(Path), ImmutablePath(Path), NotDefinedIndex(Index), ImmutableIndex(Index),
-    Expected, ExpectedArg, ExpectedType(Type, Type), ExpectedTypes(Vec<Type>, Type),
-    FunctionPatternNotFound(String, Vec<Type>, Vec<Vec<(Type, bool)>>), ValuePatternNotFound(Type, Vec<Type>),
-    InvalidHeadValue(Value), InvalidHeadCastType(Type), InvalidCastBetween(Type, Type),
-    IndexOutOfRange(usize, usize), IllegalNegativeIndex(i64),
-    NotDefinedKey(String)
+    Error(String, i32, Position), // message, exit code
+    Exit(i32, Position), // `exit` unwinds to the host, only the cli ends the process with it
+    TargetFileNotFound(String, Position), FileNotFound(String, Position), ImportNotFound(String, Position), ImportCycle(Vec<String>, Position),
+    UnexpectedEnd(Position), UnexpectedSymbol(char, Position), ExpectedSymbol(char, char, Position), ExpectedSymbols(Vec<char>, char, Position), ExpectedWord(Position),
+    ParseFloat(String, Position), ParseInt(String, Position), ParseIntOverflow(String, Position), ParseIntNegOverflow(String, Position),
+    ParseChar(String, Position), ParseBool(String, Position), ParseString(String, Position),
+    UnclosedChar(Position), UnclosedString(Position),
+    NotDefined(String, Position), AlreadyDefined(String, Position), Immutable(String, Position),
+    NotDefinedPath(Path, Position), ImmutablePath(Path, Position), NotDefinedIndex(Index, Position), ImmutableIndex(Index, Position),
+    Expected(Position), ExpectedArg(Position), ExpectedType(Type, Type, Position), ExpectedTypes(Vec<Type>, Type, Position), MissingReturn(Type, Position),
+    ExpectedFieldType(String, Type, Type, Position), // field, declared type, given type
+    FunctionPatternNotFound(String, Vec<Type>, Vec<Vec<(Type, bool)>>, Position), ValuePatternNotFound(Type, Vec<Type>, Position),
+    AmbiguousCall(String, Vec<Type>, Vec<Vec<(Type, bool)>>, Position),
+    InvalidHeadValue(Value, Position), InvalidHeadCastType(Type, Position), InvalidCastBetween(Type, Type, Position),
+    IndexOutOfRange(usize, usize, Position), IllegalNegativeIndex(i64, Position),
+    NotDefinedKey(String, Position), NoMatch(Value, Position), UnknownLabel(String, Position),
+    YieldOutsideGenerator(Position), YieldInArgument(Position)
+}
+impl Error {
+    pub fn pos(&self) -> &Position {
+        match self {
+            Self::Error(_, _, pos) | Self::Exit(_, pos) | Self::TargetFileNotFound(_, pos) | Self::FileNotFound(_, pos) | Self::ImportNotFound(_, pos) | Self::ImportCycle(_, pos) => pos,
+            Self::UnexpectedEnd(pos) | Self::UnexpectedSymbol(_, pos) | Self::ExpectedSymbol(_, _, pos) |
+            Self::ExpectedSymbols(_, _, pos) | Self::ExpectedWord(pos) => pos,
+            Self::ParseFloat(_, pos) | Self::ParseInt(_, pos) | Self::ParseIntOverflow(_, pos) | Self::ParseIntNegOverflow(_, pos) |
+            Self::ParseChar(_, pos) | Self::ParseBool(_, pos) | Self::ParseString(_, pos) => pos,
+            Self::UnclosedChar(pos) | Self::UnclosedString(pos) => pos,
+            Self::NotDefined(_, pos) | Self::AlreadyDefined(_, pos) | Self::Immutable(_, pos) => pos,
+            Self::NotDefinedPath(_, pos) | Self::ImmutablePath(_, pos) | Self::NotDefinedIndex(_, pos) | Self::ImmutableIndex(_, pos) => pos,
+            Self::Expected(pos) | Self::ExpectedArg(pos) | Self::ExpectedType(_, _, pos) | Self::ExpectedTypes(_, _, pos) | Self::MissingReturn(_, pos) | Self::ExpectedFieldType(_, _, _, pos) => pos,
+            Self::FunctionPatternNotFound(_, _, _, pos) | Self::ValuePatternNotFound(_, _, pos) | Self::AmbiguousCall(_, _, _, pos) => pos,
+            Self::InvalidHeadValue(_, pos) | Self::InvalidHeadCastType(_, pos) | Self::InvalidCastBetween(_, _, pos) => pos,
+            Self::IndexOutOfRange(_, _, pos) | Self::IllegalNegativeIndex(_, pos) => pos,
+            Self::NotDefinedKey(_, pos) | Self::NoMatch(_, pos) | Self::UnknownLabel(_, pos) => pos,
+            Self::YieldOutsideGenerator(pos) | Self::YieldInArgument(pos) => pos,
+        }
+    }
+    // the same code wherever the error happened, the main file or an imported one
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Error(_, code, _) | Self::Exit(code, _) => *code,
+            Self::TargetFileNotFound(..) | Self::FileNotFound(..) | Self::ImportNotFound(..) => EXIT_FILE_NOT_FOUND,
+            Self::UnexpectedEnd(..) | Self::UnexpectedSymbol(..) | Self::ExpectedSymbol(..) | Self::ExpectedSymbols(..) | Self::ExpectedWord(..) |
+            Self::ParseFloat(..) | Self::ParseInt(..) | Self::ParseIntOverflow(..) | Self::ParseIntNegOverflow(..) |
+            Self::ParseChar(..) | Self::ParseBool(..) | Self::ParseString(..) | Self::UnclosedChar(..) | Self::UnclosedString(..) => EXIT_SCAN,
+            _ => EXIT_RUNTIME
+        }
+    }
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Error(..) => "Error",
+            Self::Exit(..) => "Exit",
+            Self::TargetFileNotFound(..) => "TargetFileNotFound",
+            Self::FileNotFound(..) => "FileNotFound",
+            Self::ImportNotFound(..) => "ImportNotFound",
+            Self::ImportCycle(..) => "ImportCycle",
+            Self::UnexpectedEnd(..) => "UnexpectedEnd",
+            Self::UnexpectedSymbol(..) => "UnexpectedSymbol",
+            Self::ExpectedSymbol(..) => "ExpectedSymbol",
+            Self::ExpectedSymbols(..) => "ExpectedSymbols",
+            Self::ExpectedWord(..) => "ExpectedWord",
+            Self::ParseFloat(..) => "ParseFloat",
+            Self::ParseInt(..) => "ParseInt",
+            Self::ParseIntOverflow(..) => "ParseIntOverflow",
+            Self::ParseIntNegOverflow(..) => "ParseIntNegOverflow",
+            Self::ParseChar(..) => "ParseChar",
+            Self::ParseBool(..) => "ParseBool",
+            Self::ParseString(..) => "ParseString",
+            Self::UnclosedChar(..) => "UnclosedChar",
+            Self::UnclosedString(..) => "UnclosedString",
+            Self::NotDefined(..) => "NotDefined",
+            Self::AlreadyDefined(..) => "AlreadyDefined",
+            Self::Immutable(..) => "Immutable",
+            Self::NotDefinedPath(..) => "NotDefinedPath",
+            Self::ImmutablePath(..) => "ImmutablePath",
+            Self::NotDefinedIndex(..) => "NotDefinedIndex",
+            Self::ImmutableIndex(..) => "ImmutableIndex",
+            Self::Expected(..) => "Expected",
+            Self::ExpectedArg(..) => "ExpectedArg",
+            Self::ExpectedType(..) => "ExpectedType",
+            Self::ExpectedTypes(..) => "ExpectedTypes",
+            Self::ExpectedFieldType(..) => "ExpectedFieldType",
+            Self::MissingReturn(..) => "MissingReturn",
+            Self::FunctionPatternNotFound(..) => "FunctionPatternNotFound",
+            Self::ValuePatternNotFound(..) => "ValuePatternNotFound",
+            Self::AmbiguousCall(..) => "AmbiguousCall",
+            Self::InvalidHeadValue(..) => "InvalidHeadValue",
+            Self::InvalidHeadCastType(..) => "InvalidHeadCastType",
+            Self::InvalidCastBetween(..) => "InvalidCastBetween",
+            Self::IndexOutOfRange(..) => "IndexOutOfRange",
+            Self::IllegalNegativeIndex(..) => "IllegalNegativeIndex",
+            Self::NotDefinedKey(..) => "NotDefinedKey",
+            Self::NoMatch(..) => "NoMatch",
+            Self::UnknownLabel(..) => "UnknownLabel",
+            Self::YieldOutsideGenerator(..) => "YieldOutsideGenerator",
+            Self::YieldInArgument(..) => "YieldInArgument",
+        }
+    }
 }
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Error(msg) => write!(f, "ERROR: {msg}"),
-            Self::TargetFileNotFound(path) => write!(f, "ERROR: target file {path:?} couldn't be found in the current directory"),
-            Self::FileNotFound(path) => write!(f, "ERROR: {path:?} couldn't be found in the current directory"),
-            Self::UnexpectedEnd => write!(f, "ERROR: unexpected end of input"),
-            Self::UnexpectedSymbol(s) => write!(f, "ERROR: unexpected {s:?}"),
-            Self::ExpectedSymbol(expected, got) => write!(f, "ERROR: expected {expected:?}, got {got:?}"),
-            Self::ExpectedSymbols(expected, got) => write!(f, "ERROR: expected {:?}, got {got:?}", expected.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|")),
-            Self::ExpectedWord => write!(f, "ERROR: expected the beginning of a word here"),
-            Self::ParseFloat(n) => write!(f, "ERROR: {n:?} couldn't be parsed as a float"),
-            Self::ParseInt(n) => write!(f, "ERROR: {n:?} couldn't be parsed as an int"),
-            Self::ParseIntOverflow(n) => write!(f, "ERROR: {n:?} overflowed max int64 value"),
-            Self::ParseIntNegOverflow(n) => write!(f, "ERROR: {n:?} underflowed min int64 value"),
-            Self::ParseChar(n) => write!(f, "ERROR: {n:?} couldn't be parsed as an char"),
-            Self::ParseBool(n) => write!(f, "ERROR: {n:?} couldn't be parsed as an bool"),
-            Self::ParseString(n) => write!(f, "ERROR: {n:?} couldn't be parsed as an str"),
-            Self::UnclosedChar => write!(f, "ERROR: missing \"'\""),
-            Self::UnclosedString => write!(f, "ERROR: missing closing quotes"),
-            Self::NotDefined(id) => write!(f, "ERROR: {id:?} is not defined"),
-            Self::AlreadyDefined(id) => write!(f, "ERROR: {id:?} is already defined"),
-            Self::Immutable(id) => write!(f, "ERROR: {id:?} is immutable"),
-            Self::NotDefinedPath(path) => write!(f, "ERROR: {path:?} is not defined"),
-            Self::ImmutablePath(path) => write!(f, "ERROR: {path:?} is immutable"),
-            Self::NotDefinedIndex(index) => write!(f, "ERROR: {index:?} is not defined"),
-            Self::ImmutableIndex(index) => write!(f, "ERROR: {index:?} is immutable"),
-            Self::Expected => write!(f, "ERROR: expected a value for the head"),
-            Self::ExpectedArg => write!(f, "ERROR: expected a value for argument"),
-            Self::ExpectedType(t1, t2) => write!(f, "ERROR: expected {t1}, got {t2}"),
-            Self::ExpectedTypes(t, t2) => write!(f, "ERROR: expected {}, got {t2}",
+            Self::Error(msg, _, _) => write!(f, "ERROR: {msg}"),
+            Self::Exit(code, _) => write!(f, "exit with code {code}"),
+            Self::TargetFileNotFound(path, _) => write!(f, "ERROR: target file {path:?} couldn't be found in the current directory"),
+            Self::FileNotFound(path, _) => write!(f, "ERROR: {path:?} couldn't be found in the current directory"),
+            Self::ImportNotFound(path, _) => write!(f, "ERROR: {path:?} couldn't be found next to the importing file or in NODY_PATH"),
+            Self::ImportCycle(chain, _) => write!(f, "ERROR: import cycle {}", chain.iter().map(|path| format!("{path:?}")).collect::<Vec<String>>().join(" -> ")),
+            Self::UnexpectedEnd(_) => write!(f, "ERROR: unexpected end of input"),
+            Self::UnexpectedSymbol(s, _) => write!(f, "ERROR: unexpected {s:?}"),
+            Self::ExpectedSymbol(expected, got, _) => write!(f, "ERROR: expected {expected:?}, got {got:?}"),
+            Self::ExpectedSymbols(expected, got, _) => write!(f, "ERROR: expected {:?}, got {got:?}", expected.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|")),
+            Self::ExpectedWord(_) => write!(f, "ERROR: expected the beginning of a word here"),
+            Self::ParseFloat(n, _) => write!(f, "ERROR: {n:?} couldn't be parsed as a float"),
+            Self::ParseInt(n, _) => write!(f, "ERROR: {n:?} couldn't be parsed as an int"),
+            Self::ParseIntOverflow(n, _) => write!(f, "ERROR: {n:?} overflowed max int64 value"),
+            Self::ParseIntNegOverflow(n, _) => write!(f, "ERROR: {n:?} underflowed min int64 value"),
+            Self::ParseChar(n, _) => write!(f, "ERROR: {n:?} couldn't be parsed as an char"),
+            Self::ParseBool(n, _) => write!(f, "ERROR: {n:?} couldn't be parsed as an bool"),
+            Self::ParseString(n, _) => write!(f, "ERROR: {n:?} couldn't be parsed as an str"),
+            Self::UnclosedChar(_) => write!(f, "ERROR: missing \"'\""),
+            Self::UnclosedString(_) => write!(f, "ERROR: missing closing quotes"),
+            Self::NotDefined(id, _) => write!(f, "ERROR: {id:?} is not defined"),
+            Self::AlreadyDefined(id, _) => write!(f, "ERROR: {id:?} is already defined"),
+            Self::Immutable(id, _) => write!(f, "ERROR: {id:?} is immutable"),
+            Self::NotDefinedPath(path, _) => write!(f, "ERROR: {path:?} is not defined"),
+            Self::ImmutablePath(path, _) => write!(f, "ERROR: {path:?} is immutable"),
+            Self::NotDefinedIndex(index, _) => write!(f, "ERROR: {index:?} is not defined"),
+            Self::ImmutableIndex(index, _) => write!(f, "ERROR: {index:?} is immutable"),
+            Self::Expected(_) => write!(f, "ERROR: expected a value for the head"),
+            Self::ExpectedArg(_) => write!(f, "ERROR: expected a value for argument"),
+            Self::ExpectedType(t1, t2, _) => write!(f, "ERROR: expected {t1}, got {t2}"),
+            Self::ExpectedFieldType(field, t1, t2, _) => write!(f, "ERROR: expected {t1} for the field {field:?}, got {t2}"),
+            Self::ExpectedTypes(t, t2, _) => write!(f, "ERROR: expected {}, got {t2}",
             t.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|")),
-            Self::FunctionPatternNotFound(id, args, patterns) => write!(f, "ERROR: no function {id:?} found with pattern ({})\nvalid patterns would be:\n{}",
+            Self::MissingReturn(t, _) => write!(f, "ERROR: expected a return value of type {t}"),
+            Self::FunctionPatternNotFound(id, args, patterns, _) => write!(f, "ERROR: no function {id:?} found with pattern ({})\nvalid patterns would be:\n{}",
             argument_display(args), patterns_display(patterns)),
-            Self::ValuePatternNotFound(typ, args) => write!(f, "ERROR: no value function of type {typ} found with pattern ({})",
+            Self::ValuePatternNotFound(typ, args, _) => write!(f, "ERROR: no value function of type {typ} found with pattern ({})",
             argument_display(args)),
-            Self::InvalidHeadValue(v) => write!(f, "ERROR: unexpected {} value for head", v.typ()),
-            Self::InvalidHeadCastType(t) => write!(f, "ERROR: invalid cast type {t}"),
-            Self::InvalidCastBetween(t1, t2) => write!(f, "ERROR: invalid cast from {t2} to {t1}"),
-            Self::IndexOutOfRange(idx, size) => write!(f, "ERROR: index {idx} out of range of size {size}"),
-            Self::IllegalNegativeIndex(idx) => write!(f, "ERROR: illegal negative index {idx}"),
-            Self::NotDefinedKey(key) => write!(f, "ERROR: {key:?} doesn't exist in object"),
+            Self::AmbiguousCall(id, args, patterns, _) => write!(f, "ERROR: call of {id:?} with pattern ({}) is ambiguous between:\n{}",
+            argument_display(args), patterns_display(patterns)),
+            Self::InvalidHeadValue(v, _) => write!(f, "ERROR: unexpected {} value for head", v.typ()),
+            Self::InvalidHeadCastType(t, _) => write!(f, "ERROR: invalid cast type {t}"),
+            Self::InvalidCastBetween(t1, t2, _) => write!(f, "ERROR: invalid cast from {t2} to {t1}"),
+            Self::IndexOutOfRange(idx, size, _) => write!(f, "ERROR: index {idx} out of range of size {size}"),
+            Self::IllegalNegativeIndex(idx, _) => write!(f, "ERROR: illegal negative index {idx}"),
+            Self::NotDefinedKey(key, _) => write!(f, "ERROR: {key:?} doesn't exist in object"),
+            Self::NoMatch(v, _) => write!(f, "ERROR: no arm matches {v:?}"),
+            Self::UnknownLabel(label, _) => write!(f, "ERROR: no loop is labeled {label:?}"),
+            Self::YieldOutsideGenerator(_) => write!(f, "ERROR: yield outside of a generator"),
+            Self::YieldInArgument(_) => write!(f, "ERROR: a yield can't be an argument"),
         }
     }
 }
 
-pub fn print_trace(trace: &Vec<(Position)>) -> String {
+pub const RED: &str = "\x1b[1;31m";
+pub const BLUE: &str = "\x1b[1;34m";
+pub const RESET: &str = "\x1b[0m";
+pub fn use_color() -> bool {
+    std::io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+pub fn print_error(e: &Error, trace: &Vec<Position>, sources: &HashMap<String, String>, color: bool) -> String {
+    let mut trace = trace.clone();
+    if trace.first() != Some(e.pos()) { trace.insert(0, e.pos().clone()) } // scan errors have no trace yet
+    let (red, reset) = if color { (RED, RESET) } else { ("", "") };
+    format!("{red}{e}{reset}\n{}", print_trace(&trace, sources, color).trim_end())
+}
+pub fn print_trace(trace: &Vec<Position>, sources: &HashMap<String, String>, color: bool) -> String {
     let mut string = String::new();
-    for pos in trace.iter() {
-        string.push_str("in ");
-        string.push_str(pos.path.as_str());
-        string.push(':');
-        string.push_str((pos.ln.start + 1).to_string().as_str());
-        string.push(':');
-        string.push_str((pos.col.start).to_string().as_str());
-        string.push('\n');
-        let text = match std::fs::read_to_string(&pos.path) {
-            Ok(text) => text,
-            Err(_) => String::from("FILE NOT FOUND")
-        };
-        let text_lines: Vec<&str> = text.split("\n").collect();
-        let lines = text_lines.get(pos.ln.clone()).unwrap_or_else(|| &["LINES OUT OF RANGE"]);
-        for line in lines { string.push_str(line) }
+    for pos in trace.iter() { string.push_str(&print_snippet(pos, sources, color)) }
+    string
+}
+const TAB_WIDTH: usize = 4;
+// rustc like snippet of the first line of the position with the column range underlined
+pub fn print_snippet(pos: &Position, sources: &HashMap<String, String>, color: bool) -> String {
+    let (red, blue, reset) = if color { (RED, BLUE, RESET) } else { ("", "", "") };
+    let (ln, col) = (pos.ln.start + 1, pos.col.start + 1);
+    let text = match sources.get(&*pos.path) {
+        Some(text) => Some(text.clone()),
+        None => fs::read_to_string(&*pos.path).ok()
+    };
+    let line = text.as_ref().and_then(|text| text.split('\n').nth(pos.ln.start)).map(|line| line.trim_end_matches('\r'));
+    let gutter = " ".repeat(ln.to_string().len());
+    let mut string = format!("{gutter}{blue}-->{reset} {}:{ln}:{col}\n", pos.path);
+    if let Some(line) = line {
+        let len = line.chars().count();
+        let start = min(pos.col.start, len);
+        let end = if pos.ln.end > pos.ln.start + 1 { len } else { min(pos.col.end, len) }; // multiline positions are underlined to the end of the line
+        // tabs are printed as four spaces so the underline stays below the columns it marks
+        let width = |chars: &str| chars.chars().map(|c| if c == '\t' { TAB_WIDTH } else { 1 }).sum::<usize>();
+        let offset = |idx: usize| line.char_indices().nth(idx).map_or(line.len(), |(offset, _)| offset);
+        let (before, marked) = (&line[..offset(start)], &line[offset(start)..offset(end)]);
+        let carets = "^".repeat(max(width(marked), 1));
+        string.push_str(&format!("{gutter} {blue}|{reset}\n"));
+        string.push_str(&format!("{blue}{ln} |{reset} {}\n", line.replace('\t', &" ".repeat(TAB_WIDTH))));
+        string.push_str(&format!("{gutter} {blue}|{reset} {}{red}{carets}{reset}\n", " ".repeat(width(before))));
     }
     string
 }
\ No newline at end of file
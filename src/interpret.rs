@@ -1,14 +1,299 @@
 use crate::*;
 
 pub fn find_index(idx: &i64, size: usize) -> usize {
-    if *idx < 0 { size - idx.abs() as usize } else { *idx as usize }
+    if *idx < 0 { size - idx.unsigned_abs() as usize } else { *idx as usize }
 }
+// strings are indexed by chars, this is the byte offset of the char at an index
+pub fn char_offset(s: &str, idx: usize) -> usize { s.char_indices().nth(idx).map_or(s.len(), |(offset, _)| offset) }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub enum Return { None, Return, Break, Continue }
+pub enum Return { None, Return, Break, Continue, Yield }
+
+// where a suspended generator continues, recorded from the yield outwards while the yield ends the calls around it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resume {
+    Body(Position, usize, Scope), // the statement a body was at and its scope
+    Call(Position, Scope, NativFunction) // a native call with the scope holding its arguments
+}
+
+pub fn call_native_fn(func: &NativFunction, values: Vec<Value>, poses: &[Position], context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    context.call(func.inline);
+    let res = match context.create_params(&func.params, values, poses, func.inline) {
+        Ok(()) => (func.body)(context),
+        Err(e) => Err(e)
+    };
+    context.after_call(func.inline);
+    res
+}
+pub fn call_fn(func: &Function, values: Vec<Value>, poses: &[Position], context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let start = context.scopes.len();
+    match &func.env {
+        Some(env) if func.inline => context.call_inline_env(env),
+        Some(env) => context.call_env(env),
+        None => context.call(func.inline)
+    }
+    let res = match context.create_params(&func.params, values, poses, func.inline) {
+        // a function that yields only gets its arguments, it runs whenever a value is taken from it
+        Ok(()) if func.generator => Ok((Some(Value::Iterator(Iter::Generator(Box::new(Generator::new(func.clone(), context.frame_locals().to_vec()))))), Return::None)),
+        Ok(()) => eval(&func.body, context),
+        Err(e) => Err(e)
+    };
+    match &func.env {
+        Some(env) if func.inline => context.after_call_inline_env(env, start),
+        Some(env) => context.after_call_env(env),
+        None => context.after_call(func.inline)
+    }
+    let (value, ret) = res?;
+    if ret == Return::Yield { return Err(Error::YieldOutsideGenerator(func.body.pos().clone())) }
+    if let Some(return_type) = &func.return_type {
+        match &value {
+            Some(value) if !value.typ().matches(return_type) => return Err(Error::ExpectedType(return_type.clone(), value.typ(), func.body.pos().clone())),
+            None if Type::None != *return_type => return Err(Error::MissingReturn(return_type.clone(), func.body.pos().clone())),
+            None => return Ok((Some(Value::None), Return::None)),
+            _ => {}
+        }
+    }
+    Ok((value, if ret == Return::Return { Return::None } else { ret })) // a return ends the call, not its caller
+}
+// a closure runs in the frame that created it, anywhere else it is a call on its captured environment
+pub fn call_closure(closure: &Closure, context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    match &closure.env {
+        Some(env) if env.frame != context.frame_id() => {
+            context.call_env(env);
+            let res = eval(&closure.node, context);
+            context.after_call_env(env);
+            let (value, ret) = res?;
+            if ret == Return::Yield { return Err(Error::YieldOutsideGenerator(closure.node.pos().clone())) }
+            Ok((value, Return::None)) // a return ends the call, not the frame running it
+        }
+        _ => eval(&closure.node, context)
+    }
+}
+pub fn call_word(v: &String, word_pos: &Position, pos: &Position, values: Vec<Value>, types: Vec<Type>, poses: &[Position], context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let callee = match context.resolve_fn(v, &types, word_pos) {
+        Ok(callee) => callee,
+        Err(e) => {
+            context.trace_push(word_pos);
+            return Err(e)
+        }
+    };
+    match callee {
+        Some(callee) => {
+            let res = match &callee {
+                Callee::Native(func) => call_native_fn(func, values, poses, context),
+                Callee::Fn(func) => call_fn(func, values, poses, context)
+            };
+            match (res, callee) {
+                (Ok(res), Callee::Native(func)) => Ok(end_native_call(res, func, pos, context)),
+                (Ok(res), _) => {
+                    context.pop();
+                    Ok(res)
+                }
+                (Err(e), _) => {
+                    context.trace_push(pos);
+                    Err(e)
+                }
+            }
+        }
+        None => match context.get_var(v) {
+            Some(head_value) => {
+                let head_value = head_value.clone();
+                call_value(head_value, word_pos, pos, values, types, poses, context)
+            }
+            None => if context.fn_exists(v) || context.native_fn_exists(v) {
+                context.trace_push(word_pos);
+                Err(Error::FunctionPatternNotFound(v.clone(), types, context.get_patterns(v).unwrap(), word_pos.clone()))
+            } else {
+                context.trace_push(word_pos);
+                Err(Error::NotDefined(v.clone(), word_pos.clone()))
+            }
+        }
+    }
+}
+// a native call that yields keeps its scope to continue there when the generator resumes
+fn end_native_call(res: (Option<Value>, Return), func: NativFunction, pos: &Position, context: &mut Context) -> (Option<Value>, Return) {
+    let scope = context.pop();
+    if let (Return::Yield, Some(scope)) = (res.1, scope) {
+        context.resume.push(Resume::Call(pos.clone(), scope, func));
+    }
+    res
+}
+// a struct constructor names the field that got the wrong type
+fn field_type_error(typ: &Type, types: &[Type], poses: &[Position], context: &Context) -> Option<Error> {
+    let (Type::Struct(_) | Type::Variant(_, _)) = typ else { return None };
+    let id = typ.to_string();
+    let fields = &context.get_scope_native_fn_any(&id)?.get_native_fn_first(&id)?.params;
+    if fields.len() != types.len() { return None }
+    let ((field, field_type, _), (arg_type, pos)) = fields.iter().zip(types.iter().zip(poses)).find(|((_, field_type, _), (arg_type, _))| !arg_type.matches(field_type))?;
+    Some(Error::ExpectedFieldType(field.clone(), field_type.clone(), arg_type.clone(), pos.clone()))
+}
+pub fn call_value(head_value: Value, head_pos: &Position, pos: &Position, values: Vec<Value>, types: Vec<Type>, poses: &[Position], context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let callable = matches!(head_value, Value::Function(_) | Value::NativFunction(_));
+    if types.is_empty() && !callable {
+        context.pop();
+        return Ok((Some(head_value), Return::None))
+    }
+    match head_value {
+        Value::Type(typ) => match context.get_native_fn(&typ.to_string(), &types) {
+            Some(func) => {
+                let func = func.clone();
+                let res = call_native_fn(&func, values, poses, context)?;
+                context.pop();
+                Ok(res)
+            }
+            None => if context.fn_exists(&typ.to_string()) || context.native_fn_exists(&typ.to_string()) {
+                if let Some(e) = field_type_error(&typ, &types, poses, context) {
+                    context.trace_push(e.pos());
+                    return Err(e)
+                }
+                context.trace_push(pos);
+                Err(Error::InvalidCastBetween(typ.clone(), types[0].clone(), pos.clone()))
+            } else {
+                context.trace_push(head_pos);
+                Err(Error::InvalidHeadCastType(typ.clone(), head_pos.clone()))
+            }
+        }
+        Value::Vector(vec_values, _typ) => if values.len() == 1 {
+            context.pop();
+            match &values[0] {
+                Value::Int(idx) => {
+                    let idx = find_index(idx, vec_values.len());
+                    match vec_values.get(idx) {
+                        Some(value) => Ok((Some(value.clone()), Return::None)),
+                        None => {
+                            context.trace_push(&poses[0]);
+                            Err(Error::IndexOutOfRange(idx, vec_values.len(), poses[0].clone()))
+                        }
+                    }
+                }
+                _ => {
+                    context.trace_push(&poses[0]);
+                    Err(Error::ExpectedTypes(vec![Type::Int], types[0].clone(), poses[0].clone()))
+                }
+            }
+        } else {
+            context.trace_push(&poses[0]);
+            Err(Error::ValuePatternNotFound(Type::Vector(Some(Box::new(Type::Any))), types, poses[0].clone()))
+        }
+        Value::Object(_) | Value::Struct(_) => if values.len() == 1 {
+            context.pop();
+            match &values[0] {
+                Value::Key(key) => {
+                    let field = match &head_value {
+                        Value::Struct(v) => v.get(key),
+                        Value::Object(obj_scope) => obj_scope.get_var(key),
+                        _ => None
+                    };
+                    match field {
+                        Some(value) => Ok((Some(value.clone()), Return::None)),
+                        None => {
+                            context.trace_push(&poses[0]);
+                            Err(Error::NotDefinedKey(key.clone(), poses[0].clone()))
+                        }
+                    }
+                }
+                _ => {
+                    context.trace_push(&poses[0]);
+                    Err(Error::ExpectedTypes(vec![Type::Key], types[0].clone(), poses[0].clone()))
+                }
+            }
+        } else {
+            context.trace_push(&poses[0]);
+            Err(Error::ValuePatternNotFound(Type::Vector(Some(Box::new(Type::Any))), types, poses[0].clone()))
+        }
+        Value::String(string) => if values.len() == 1 {
+            context.pop();
+            if let Value::Int(idx) = &values[0] {
+                let len = string.chars().count();
+                let idx = find_index(idx, len);
+                match string.chars().nth(idx) {
+                    Some(c) => Ok((Some(Value::Char(c)), Return::None)),
+                    None => {
+                        context.trace_push(&poses[0]);
+                        Err(Error::IndexOutOfRange(idx, len, poses[0].clone()))
+                    }
+                }
+            } else {
+                context.trace_push(&poses[0]);
+                Err(Error::ExpectedTypes(vec![Type::Int], types[0].clone(), poses[0].clone()))
+            }
+        } else if values.len() == 2 {
+            context.pop();
+            if let Value::Int(idx1) = &values[0] {
+                if let Value::Int(idx2) = &values[1] {
+                    if *idx1 < 0 {
+                        context.trace_push(&poses[0]);
+                        return Err(Error::IllegalNegativeIndex(*idx1, poses[0].clone()))
+                    }
+                    if *idx2 < 0 {
+                        context.trace_push(&poses[1]);
+                        return Err(Error::IllegalNegativeIndex(*idx2, poses[1].clone()))
+                    }
+                    let len = string.chars().count();
+                    if *idx1 as usize >= len {
+                        context.trace_push(&poses[0]);
+                        return Err(Error::IndexOutOfRange(*idx1 as usize, len, poses[0].clone()))
+                    }
+                    if *idx2 as usize >= len {
+                        context.trace_push(&poses[0]);
+                        return Err(Error::IndexOutOfRange(*idx2 as usize, len, poses[0].clone()))
+                    }
+                    let (start, end) = (char_offset(&string, *idx1 as usize), char_offset(&string, *idx2 as usize));
+                    Ok((Some(Value::String(string.get(start..end).unwrap().to_string())), Return::None))
+                } else {
+                    context.trace_push(&poses[0]);
+                    Err(Error::ExpectedTypes(vec![Type::Int], types[0].clone(), poses[0].clone()))
+                }
+            } else {
+                context.trace_push(&poses[0]);
+                Err(Error::ExpectedTypes(vec![Type::Int], types[0].clone(), poses[0].clone()))
+            }
+        } else {
+            context.trace_push(&poses[0]);
+            Err(Error::ValuePatternNotFound(Type::Vector(Some(Box::new(Type::Any))), types, poses[0].clone()))
+        }
+        Value::Function(func) => {
+            match call_fn(&func, values, poses, context) {
+                Ok(res) => {
+                    context.pop();
+                    Ok(res)
+                }
+                Err(e) => {
+                    context.trace_push(pos);
+                    Err(e)
+                }
+            }
+        }
+        Value::NativFunction(func) => {
+            match call_native_fn(&func, values, poses, context) {
+                Ok(res) => {
+                    context.pop();
+                    Ok(res)
+                }
+                Err(e) => {
+                    context.trace_push(pos);
+                    Err(e)
+                }
+            }
+        }
+        _ => {
+            context.trace_push(head_pos);
+            Err(Error::InvalidHeadValue(head_value.clone(), head_pos.clone()))
+        }
+    }
+}
+pub fn eval(node: &Rc<Node>, context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    match &mut context.vm {
+        Some(vm) => {
+            let chunk = vm.chunk(node);
+            execute(&chunk, context)
+        }
+        None => interpret(node, context)
+    }
+}
 pub fn interpret(node: &Node, context: &mut Context) -> Result<(Option<Value>, Return), Error> {
     match node {
-        Node::None { pos:_ } => Ok((None, Return::None)),
+        Node::None { pos:_ } => Ok((Some(Value::None), Return::None)),
         Node::Int { v, pos:_ } => Ok((Some(Value::Int(*v)), Return::None)),
         Node::Float { v, pos:_ } => Ok((Some(Value::Float(*v)), Return::None)),
         Node::Char { v, pos:_ } => Ok((Some(Value::Char(*v)), Return::None)),
@@ -21,14 +306,16 @@ pub fn interpret(node: &Node, context: &mut Context) -> Result<(Option<Value>, R
                 let (value, _) = interpret(n, context)?;
                 if value.is_none() {
                     context.trace_push(n.pos());
-                    return Err(Error::Expected)
+                    return Err(Error::Expected(n.pos().clone()))
                 }
                 let value = value.unwrap();
-                if typ.is_none() {
-                    typ = Some(value.typ());
-                } else if typ != Some(value.typ()) {
-                    context.trace_push(n.pos());
-                    return Err(Error::ExpectedType(typ.unwrap(), value.typ()))
+                match &typ {
+                    None => typ = Some(value.typ()),
+                    Some(first) if *first != value.typ() => {
+                        context.trace_push(n.pos());
+                        return Err(Error::ExpectedType(first.clone(), value.typ(), n.pos().clone()))
+                    }
+                    _ => {}
                 }
                 values.push(value);
             }
@@ -37,13 +324,13 @@ pub fn interpret(node: &Node, context: &mut Context) -> Result<(Option<Value>, R
         Node::Type { v, pos:_ } => Ok((Some(Value::Type(v.clone())), Return::None)),
         Node::Word { v, pos } => match context.get_var(v) {
             Some(v) => Ok((Some(v.clone()), Return::None)),
-            None => {
+            None => if let Some(typ) = context.get_type(v) { Ok((Some(Value::Type(typ)), Return::None)) } else {
                 context.trace_push(pos);
-                Err(Error::NotDefined(v.clone()))
+                Err(Error::NotDefined(v.clone(), pos.clone()))
             }
         }
         Node::Key { v, pos:_ } => Ok((Some(Value::Key(v.clone())), Return::None)),
-        Node::Closure { node, pos } => Ok((Some(Value::Closure(node.as_ref().clone())), Return::None)),
+        Node::Closure { node, pos: _ } => Ok((Some(Value::Closure(Closure::new(node.clone(), context.capture(node)))), Return::None)),
         Node::Params { params: node_params, pos } => {
             let mut params: Vec<(String, Type, bool)> = vec![];
             for (param, type_node, more) in node_params.iter() {
@@ -53,16 +340,16 @@ pub fn interpret(node: &Node, context: &mut Context) -> Result<(Option<Value>, R
                         params.push((param.clone(), typ, *more));
                     } else {
                         context.trace_push(type_node.pos());
-                        return Err(Error::ExpectedType(Type::Type, typ.typ()))
+                        return Err(Error::ExpectedType(Type::Type, typ.typ(), type_node.pos().clone()))
                     }
                 } else {
                     context.trace_push(pos);
-                    return Err(Error::Expected)
+                    return Err(Error::Expected(pos.clone()))
                 }
             }
             Ok((Some(Value::Params(params)), Return::None))
         }
-        Node::Object { entries, pos } => {
+        Node::Object { entries, pos: _ } => {
             let mut obj_scope = Scope::new();
             for (key, value_node) in entries.iter() {
                 let (value, _) = interpret(value_node, context)?;
@@ -70,17 +357,33 @@ pub fn interpret(node: &Node, context: &mut Context) -> Result<(Option<Value>, R
                     obj_scope.create_var(key.clone(), value, true, value_node.pos().clone(), true)?;
                 } else {
                     context.trace_push(value_node.pos());
-                    return Err(Error::Expected)
+                    return Err(Error::Expected(value_node.pos().clone()))
                 }
             }
             Ok((Some(Value::Object(obj_scope)), Return::None))
         }
-        Node::Body { nodes, pos:_ } => {
-            context.push();
-            for node in nodes.iter() {
+        Node::Body { nodes, pos } => {
+            // a resumed generator continues at the statement it yielded from
+            let start = match context.resume.last() {
+                Some(Resume::Body(body_pos, _, _)) if body_pos == pos => match context.resume.pop() {
+                    Some(Resume::Body(_, idx, scope)) => {
+                        context.scopes.push(scope);
+                        idx
+                    }
+                    _ => 0
+                }
+                _ => {
+                    context.push();
+                    0
+                }
+            };
+            for (idx, node) in nodes.iter().enumerate().skip(start) {
                 let (value, ret) = interpret(node, context)?;
                 if ret != Return::None {
-                    context.pop();
+                    let scope = context.pop();
+                    if let (Return::Yield, Some(scope)) = (ret, scope) {
+                        context.resume.push(Resume::Body(pos.clone(), idx, scope));
+                    }
                     return Ok((value, ret))
                 }
             }
@@ -88,225 +391,52 @@ pub fn interpret(node: &Node, context: &mut Context) -> Result<(Option<Value>, R
             Ok((None, Return::None))
         }
         Node::Node { head, args, pos } => {
+            if let Some(Resume::Call(call_pos, _, _)) = context.resume.last() {
+                if call_pos == pos {
+                    let Some(Resume::Call(_, scope, func)) = context.resume.pop() else { unreachable!() };
+                    // the innermost call is the yield the generator stopped at, it is done
+                    if context.resume.is_empty() { return Ok((None, Return::None)) }
+                    // the arguments are already in the scope
+                    context.scopes.push(scope);
+                    return match (func.body)(context) {
+                        Ok(res) => Ok(end_native_call(res, func, pos, context)),
+                        Err(e) => {
+                            context.trace_push(pos);
+                            Err(e)
+                        }
+                    }
+                }
+            }
             context.push();
             // get arguments
             let mut values: Vec<Value> = vec![];
             let mut types: Vec<Type> = vec![];
             let mut poses: Vec<Position> = vec![];
             for arg in args.iter() {
-                if let (Some(value), _) = interpret(arg, context)? {
+                let (value, ret) = interpret(arg, context)?;
+                if ret == Return::Yield {
+                    context.trace_push(arg.pos());
+                    return Err(Error::YieldInArgument(arg.pos().clone()))
+                }
+                if let Some(value) = value {
                     poses.push(arg.pos().clone());
                     types.push(value.typ());
                     values.push(value);
                 } else {
                     context.trace_push(arg.pos());
-                    return Err(Error::ExpectedArg)
+                    return Err(Error::ExpectedArg(arg.pos().clone()))
                 }
             }
             // try to get a function
             if let Node::Word { v, pos: word_pos } = head.as_ref() {
-                match context.get_native_fn(v, &types) {
-                    Some(func) => {
-                        let mut func_context = Context::call(context.get_fn_pos(v, &types).unwrap().path.clone(), context, func.inline);
-                        func_context.create_params(&func.params, values, poses, func.inline)?;
-                        match (func.body)(&mut func_context) {
-                            Ok(res) => {
-                                context.after_call(func_context, func.inline);
-                                context.pop();
-                                return Ok(res)
-                            }
-                            Err(e) => {
-                                context.after_call(func_context, func.inline);
-                                context.trace_push(pos);
-                                return Err(e)
-                            }
-                        }
-                    }
-                    None => match context.get_fn(v, &types) {
-                        Some(func) => {
-                            let mut func_context = Context::call(context.get_fn_pos(v, &types).unwrap().path.clone(), context, func.inline);
-                            func_context.create_params(&func.params, values, poses, func.inline)?;
-                            match interpret(&func.body, &mut func_context) {
-                                Ok(res) => {
-                                    context.after_call(func_context, func.inline);
-                                    context.pop();
-                                    return Ok(res)
-                                }
-                                Err(e) => {
-                                    context.after_call(func_context, func.inline);
-                                    context.trace_push(pos);
-                                    return Err(e)
-                                }
-                            }
-                        }
-                        None => match context.get_var(v) {
-                            Some(_) => {}
-                            None => if context.fn_exists(v) || context.native_fn_exists(v) {
-                                context.trace_push(word_pos);
-                                return Err(Error::FunctionPatternNotFound(v.clone(), types, context.get_patterns(v).unwrap()))
-                            } else {
-                                context.trace_push(word_pos);
-                                return Err(Error::NotDefined(v.clone()))
-                            }
-                        }
-                    }
-                }
+                return call_word(v, word_pos, pos, values, types, &poses, context)
             }
             // not a function
             if let Some(head_value) = interpret(head, context)?.0 {
-                if types.len() == 0 {
-                    context.pop();
-                    return Ok((Some(head_value), Return::None))
-                }
-                match head_value {
-                    Value::Type(typ) => match context.get_native_fn(&typ.to_string(), &types) {
-                        Some(func) => {
-                            let mut func_context = Context::call(context.get_fn_pos(&typ.to_string(), &types).unwrap().path.clone(), context, func.inline);
-                            func_context.create_params(&func.params, values, poses, func.inline)?;
-                            let res = (func.body)(&mut func_context)?;
-                            context.after_call(func_context, func.inline);
-                            context.pop();
-                            return Ok(res)
-                        }
-                        None => if context.fn_exists(&typ.to_string()) || context.native_fn_exists(&typ.to_string()) {
-                            context.trace_push(pos);
-                            Err(Error::InvalidCastBetween(typ.clone(), types[0].clone()))
-                        } else {
-                            context.trace_push(head.pos());
-                            Err(Error::InvalidHeadCastType(typ.clone()))
-                        }
-                    }
-                    Value::Vector(vec_values, typ) => if values.len() == 1 {
-                        context.pop();
-                        match &values[0] {
-                            Value::Int(idx) => {
-                                let idx = find_index(idx, vec_values.len());
-                                match vec_values.get(idx) {
-                                    Some(value) => Ok((Some(value.clone()), Return::None)),
-                                    None => {
-                                        context.trace_push(&poses[0]);
-                                        Err(Error::IndexOutOfRange(idx, vec_values.len()))
-                                    }
-                                }
-                            }
-                            _ => {
-                                context.trace_push(&poses[0]);
-                                Err(Error::ExpectedTypes(vec![Type::Int], types[0].clone()))
-                            }
-                        }
-                    } else {
-                        context.trace_push(&poses[0]);
-                        Err(Error::ValuePatternNotFound(Type::Vector(Some(Box::new(Type::Any))), types))
-                    }
-                    Value::Object(obj_scope) => if values.len() == 1 {
-                        context.pop();
-                        match &values[0] {
-                            Value::Key(key) => {
-                                match obj_scope.get_var(key) {
-                                    Some(value) => Ok((Some(value.clone()), Return::None)),
-                                    None => {
-                                        context.trace_push(&poses[0]);
-                                        Err(Error::NotDefinedKey(key.clone()))
-                                    }
-                                }
-                            }
-                            _ => {
-                                context.trace_push(&poses[0]);
-                                Err(Error::ExpectedTypes(vec![Type::Key], types[0].clone()))
-                            }
-                        }
-                    } else {
-                        context.trace_push(&poses[0]);
-                        Err(Error::ValuePatternNotFound(Type::Vector(Some(Box::new(Type::Any))), types))
-                    }
-                    Value::String(string) => if values.len() == 1 {
-                        context.pop();
-                        if let Value::Int(idx) = &values[0] {
-                            let idx = find_index(idx, string.len());
-                            match string.get(idx .. idx + 1) {
-                                Some(c) => Ok((Some(Value::Char(c.chars().collect::<Vec<char>>()[0])), Return::None)),
-                                None => {
-                                    context.trace_push(&poses[0]);
-                                    Err(Error::IndexOutOfRange(idx, string.len()))
-                                }
-                            }
-                        } else {
-                            context.trace_push(&poses[0]);
-                            Err(Error::ExpectedTypes(vec![Type::Int], types[0].clone()))
-                        }
-                    } else if values.len() == 2 {
-                        context.pop();
-                        if let Value::Int(idx1) = &values[0] {
-                            if let Value::Int(idx2) = &values[1] {
-                                if *idx1 < 0 {
-                                    context.trace_push(&poses[0]);
-                                    return Err(Error::IllegalNegativeIndex(*idx1))
-                                }
-                                if *idx2 < 0 {
-                                    context.trace_push(&poses[1]);
-                                    return Err(Error::IllegalNegativeIndex(*idx2))
-                                }
-                                if *idx1 as usize >= string.len() {
-                                    context.trace_push(&poses[0]);
-                                    return Err(Error::IndexOutOfRange(*idx1 as usize, string.len()))
-                                }
-                                if *idx2 as usize >= string.len() {
-                                    context.trace_push(&poses[0]);
-                                    return Err(Error::IndexOutOfRange(*idx2 as usize, string.len()))
-                                }
-                                Ok((Some(Value::String(string.get(*idx1 as usize .. *idx2 as usize).unwrap().to_string())), Return::None))
-                            } else {
-                                context.trace_push(&poses[0]);
-                                Err(Error::ExpectedTypes(vec![Type::Int], types[0].clone()))
-                            }
-                        } else {
-                            context.trace_push(&poses[0]);
-                            Err(Error::ExpectedTypes(vec![Type::Int], types[0].clone()))
-                        }
-                    } else {
-                        context.trace_push(&poses[0]);
-                        Err(Error::ValuePatternNotFound(Type::Vector(Some(Box::new(Type::Any))), types))
-                    }
-                    Value::Function(func) => {
-                        let mut func_context = Context::call(context.path.clone(), context, func.inline);
-                        func_context.create_params(&func.params, values, poses, func.inline)?;
-                        match interpret(&func.body, &mut func_context) {
-                            Ok(res) => {
-                                context.after_call(func_context, func.inline);
-                                context.pop();
-                                Ok(res)
-                            }
-                            Err(e) => {
-                                context.after_call(func_context, func.inline);
-                                context.trace_push(pos);
-                                Err(e)
-                            }
-                        }
-                    }
-                    Value::NativFunction(func) => {
-                        let mut func_context = Context::call(context.path.clone(), context, func.inline);
-                        func_context.create_params(&func.params, values, poses, func.inline)?;
-                        match (func.body)(&mut func_context) {
-                            Ok(res) => {
-                                context.after_call(func_context, func.inline);
-                                context.pop();
-                                return Ok(res)
-                            }
-                            Err(e) => {
-                                context.after_call(func_context, func.inline);
-                                context.trace_push(pos);
-                                return Err(e)
-                            }
-                        }
-                    }
-                    _ => {
-                        context.trace_push(head.pos());
-                        Err(Error::InvalidHeadValue(head_value.clone()))
-                    }
-                }
+                call_value(head_value, head.pos(), pos, values, types, &poses, context)
             } else {
                 context.trace_push(head.pos());
-                Err(Error::Expected)
+                Err(Error::Expected(head.pos().clone()))
             }
         }
     }
@@ -0,0 +1,177 @@
+use crate::*;
+
+// a lazy sequence of values, the state of the walk is part of the value so every copy walks on its own
+#[derive(Clone, PartialEq)]
+pub enum Iter {
+    Range(i64, i64, i64), // next, end, step
+    Values(Rc<Vec<Value>>, usize),
+    Chars(Rc<str>, usize), // byte offset of the next char
+    Next(Function), // the `next` function of an object, it ends by returning `()`
+    Map(Box<Iter>, Function),
+    Filter(Box<Iter>, Function),
+    Take(Box<Iter>, usize),
+    Zip(Box<Iter>, Box<Iter>),
+    Enumerate(Box<Iter>, i64),
+    Generator(Box<Generator>)
+}
+// an entry of an object, an index with its value or the values of a zip
+fn pair(v1: Value, v2: Value) -> Value { Value::Vector(vec![v1, v2], Some(Type::Any)) }
+fn call(func: &Function, values: Vec<Value>, context: &mut Context, pos: &Position) -> Result<Value, Error> {
+    let types: Vec<Type> = values.iter().map(Value::typ).collect();
+    if !func.pattern_match(&types) { return Err(Error::ValuePatternNotFound(Value::Function(func.clone()).typ(), types, pos.clone())) }
+    let poses = vec![pos.clone(); values.len()];
+    Ok(call_fn(func, values, &poses, context)?.0.unwrap_or(Value::None))
+}
+impl Iter {
+    // what `for` walks: the values of a vector, the chars of a string, the `[@key value]` entries of an object
+    // or the values the `next` function of an object returns
+    pub fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Iterator(iter) => Some(iter),
+            Value::Vector(values, _) => Some(Self::Values(Rc::new(values), 0)),
+            Value::String(string) => Some(Self::Chars(string.into(), 0)),
+            Value::Object(scope) => match scope.get_var(&"next".to_string()) {
+                Some(Value::Function(next)) => Some(Self::Next(next.clone())),
+                _ => {
+                    let mut keys: Vec<&String> = scope.vars.keys().collect();
+                    keys.sort();
+                    let entries = keys.into_iter().map(|key| pair(Value::Key(key.clone()), scope.vars[key].0.clone())).collect();
+                    Some(Self::Values(Rc::new(entries), 0))
+                }
+            }
+            Value::Struct(v) => {
+                let entries = v.fields.iter().zip(v.values).map(|((field, _, _), value)| pair(Value::Key(field.clone()), value)).collect();
+                Some(Self::Values(Rc::new(entries), 0))
+            }
+            _ => None
+        }
+    }
+    pub fn next(&mut self, context: &mut Context, pos: &Position) -> Result<Option<Value>, Error> {
+        match self {
+            Self::Range(next, end, step) => {
+                if (*step > 0 && *next >= *end) || (*step < 0 && *next <= *end) { return Ok(None) }
+                let v = *next;
+                *next += *step;
+                Ok(Some(Value::Int(v)))
+            }
+            Self::Values(values, idx) => {
+                let v = values.get(*idx).cloned();
+                *idx += 1;
+                Ok(v)
+            }
+            Self::Chars(string, idx) => match string[*idx..].chars().next() {
+                Some(c) => {
+                    *idx += c.len_utf8();
+                    Ok(Some(Value::Char(c)))
+                }
+                None => Ok(None)
+            }
+            Self::Next(next) => match call(next, vec![], context, pos)? {
+                Value::None => Ok(None),
+                v => Ok(Some(v))
+            }
+            Self::Map(iter, func) => match iter.next(context, pos)? {
+                Some(v) => Ok(Some(call(func, vec![v], context, pos)?)),
+                None => Ok(None)
+            }
+            Self::Filter(iter, func) => {
+                while let Some(v) = iter.next(context, pos)? {
+                    match call(func, vec![v.clone()], context, pos)? {
+                        Value::Bool(true) => return Ok(Some(v)),
+                        Value::Bool(false) => {}
+                        keep => return Err(Error::ExpectedType(Type::Bool, keep.typ(), pos.clone()))
+                    }
+                }
+                Ok(None)
+            }
+            Self::Take(iter, n) => {
+                if *n == 0 { return Ok(None) }
+                *n -= 1;
+                iter.next(context, pos)
+            }
+            Self::Zip(iter1, iter2) => {
+                let Some(v1) = iter1.next(context, pos)? else { return Ok(None) };
+                let Some(v2) = iter2.next(context, pos)? else { return Ok(None) };
+                Ok(Some(pair(v1, v2)))
+            }
+            Self::Enumerate(iter, idx) => match iter.next(context, pos)? {
+                Some(v) => {
+                    *idx += 1;
+                    Ok(Some(pair(Value::Int(*idx - 1), v)))
+                }
+                None => Ok(None)
+            }
+            Self::Generator(generator) => generator.resume(context)
+        }
+    }
+    // runs the iterator to its end
+    pub fn collect(mut self, context: &mut Context, pos: &Position) -> Result<Value, Error> {
+        let mut values = vec![];
+        while let Some(v) = self.next(context, pos)? { values.push(v); }
+        let typ = match values.first() {
+            Some(first) if values.iter().all(|v| exact_type(&v.typ(), &first.typ())) => Some(first.typ()),
+            Some(_) => Some(Type::Any),
+            None => None
+        };
+        Ok(Value::Vector(values, typ))
+    }
+}
+// what can be walked by `for` and turned into an iterator
+pub fn iterable() -> Type { Type::Union(vec![Type::Iterator, Type::Vector(Some(Box::new(Type::Any))), Type::String, Type::Object]) }
+impl FromValue for Iter {
+    fn typ() -> Type { iterable() }
+    fn from_value(value: Value) -> Option<Self> { Iter::from_value(value) }
+}
+impl IntoValue for Iter {
+    fn typ() -> Type { Type::Iterator }
+    fn into_value(self) -> Value { Value::Iterator(self) }
+}
+// a call of a function that yields, every value taken from it runs the function until its next yield
+#[derive(Clone, PartialEq)]
+pub struct Generator {
+    pub func: Function,
+    pub scopes: Vec<Scope>, // the local scopes of its frame
+    pub resume: Vec<Resume>,
+    pub frame: Option<usize>, // the id its frame keeps, closures created in it belong to that frame
+    pub done: bool
+}
+impl Generator {
+    pub fn new(func: Function, scopes: Vec<Scope>) -> Self { Self { func, scopes, resume: vec![], frame: None, done: false } }
+    pub fn resume(&mut self, context: &mut Context) -> Result<Option<Value>, Error> {
+        if self.done { return Ok(None) }
+        match &self.func.env {
+            Some(env) => context.call_env(env),
+            None => context.call(false)
+        }
+        let Some(frame) = context.frames.last_mut() else { return Ok(None) };
+        frame.id = *self.frame.get_or_insert(frame.id);
+        let generator = context.generator.replace(frame.id);
+        let start = frame.start + self.func.env.is_some() as usize;
+        context.scopes.truncate(start);
+        for scope in self.scopes.iter_mut() { scope.load_cells(); }
+        context.scopes.append(&mut self.scopes);
+        let resume = std::mem::replace(&mut context.resume, std::mem::take(&mut self.resume));
+        let vm = context.vm.take(); // the vm can't suspend a chunk, so generators are interpreted
+        let res = interpret(&self.func.body, context);
+        context.vm = vm;
+        let res = match res {
+            Ok((value, Return::Yield)) => {
+                self.scopes = context.scopes.split_off(start);
+                for scope in self.scopes.iter() { scope.sync_cells(); }
+                self.resume = std::mem::take(&mut context.resume);
+                Ok(Some(value.unwrap_or(Value::None)))
+            }
+            res => {
+                self.done = true;
+                res.map(|_| None)
+            }
+        };
+        context.resume = resume;
+        context.generator = generator;
+        match &self.func.env {
+            Some(env) => context.after_call_env(env),
+            None => context.after_call(false)
+        }
+        res
+    }
+}
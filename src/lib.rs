@@ -0,0 +1,462 @@
+#![allow(clippy::result_large_err, clippy::ptr_arg)] // errors carry their position and values, `&String` and `&Vec` params are the api of every scope lookup
+pub mod errors;
+pub mod value;
+pub mod context;
+pub mod scan;
+pub mod interpret;
+pub mod compile;
+pub mod vm;
+pub mod native;
+pub mod iter;
+pub mod nody_std;
+pub mod engine;
+pub use errors::*;
+pub use value::*;
+pub use context::*;
+pub use scan::*;
+pub use interpret::*;
+pub use compile::*;
+pub use vm::*;
+pub use native::*;
+pub use iter::*;
+pub use nody_std::*;
+pub use engine::*;
+use std::ops::{Range};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::fmt::{Debug, Display};
+use core::num::IntErrorKind;
+use std::cmp::{min, max};
+use std::io::{Write, IsTerminal};
+use std::{fs, env};
+
+pub fn run(path: &String, text: String, std_path: Option<String>) -> Result<(Option<Value>, Return), Error> {
+    let mut context = std_context(path.clone(), std_path)?;
+    interpret(&scan_file(path, text)?, &mut context)
+}
+pub fn scan_context(path: &String, text: String, context: &mut Context) -> Result<Node, Error> {
+    context.sources.insert(path.clone(), text.clone());
+    scan_file(path, text)
+}
+pub fn run_node(node: &Node, context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    match context.vm {
+        Some(_) => execute(&compile(node), context),
+        None => interpret(node, context)
+    }
+}
+pub fn run_context(path: &String, text: String, context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let node = scan_context(path, text, context)?;
+    run_node(&node, context)
+}
+pub fn run_file(path: &String, std_path: Option<String>) -> Result<(Option<Value>, Return), Error> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => run(path, text, std_path),
+        Err(_) => Err(Error::TargetFileNotFound(path.clone(), Position::new(0..0, 0..0, path)))
+    }
+}
+pub fn run_file_context(path: &String, context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => run_context(path, text, context),
+        Err(_) => Err(Error::TargetFileNotFound(path.clone(), Position::new(0..0, 0..0, path)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn context() {
+        let mut context = Context::new("".to_string(), Some("".to_string()));
+        assert_eq!(context.scopes.len(), 1); // first scope exists
+        context.push();                      // second scope
+        assert_eq!(context.scopes.len(), 2); // 2 scopes
+        let scope = context.pop();           // pop second scope
+        assert!(scope.is_some());            // is a scope
+        assert_eq!(context.scopes.len(), 1); // 1 scope
+    }
+    #[test]
+    fn context_vars() -> Result<(), Error> {
+        let mut context = Context::new("".to_string(), Some("".to_string()));
+        let path = String::from("<test>");
+        let pos = Position::new(0..0, 0..0, &path);
+        let x = String::from("x");
+        context.create_var(x.clone(), Value::Int(1), false, pos.clone(), false)?;     // x definition in first scope
+        assert_eq!(context.get_var(&x), Some(&Value::Int(1)));                        // x accessable correctly
+        context.push();                                                               // second scope
+        assert_eq!(context.get_var(&x), Some(&Value::Int(1)));                        // x still accessable
+        let y = String::from("y"); 
+        context.create_var(y.clone(), Value::Bool(true), false, pos.clone(), false)?; // y definition in second scope
+        assert_eq!(context.get_var(&y), Some(&Value::Bool(true)));                    // y accessable correctly
+        assert_eq!(context.get_var(&x), Some(&Value::Int(1)));                        // x in first scope still accessable
+        context.pop();                                                                // delete second scope
+        assert_eq!(context.get_var(&y), None);                                        // y deleted
+        Ok(())
+    }
+    #[test]
+    fn context_frames() -> Result<(), Error> {
+        let mut context = Context::new("".to_string(), Some("".to_string()));
+        let path = String::from("<test>");
+        let pos = Position::new(0..0, 0..0, &path);
+        let (x, g) = (String::from("x"), String::from("g"));
+        context.create_var(x.clone(), Value::Int(1), false, pos.clone(), false)?;           // x local to the first frame
+        context.global.create_var(g.clone(), Value::Int(2), false, pos.clone(), false)?;    // g global
+        context.call(false);                                                                // non-inline call
+        assert_eq!(context.get_var(&x), None);                                              // x hidden from the call
+        assert_eq!(context.get_var(&g), Some(&Value::Int(2)));                              // g shared with the call
+        context.after_call(false);
+        assert_eq!(context.scopes.len(), 1);                                                // call scopes removed
+        assert_eq!(context.get_var(&x), Some(&Value::Int(1)));                              // x accessable again
+        context.call(true);                                                                 // inline call
+        assert_eq!(context.get_var(&x), Some(&Value::Int(1)));                              // x visible inline
+        context.after_call(true);
+        Ok(())
+    }
+    #[test]
+    fn vm_matches_interpret() -> Result<(), Error> {
+        let path = String::from("<test>");
+        let text = String::from("(mut @s 0) (for @i 5 #(set @s (+ s i))) (def @g $(v vec) #(return (v 2))) (return [s (g [1 2 3])])");
+        let mut context = std_context(path.clone(), None)?;
+        let res = run_context(&path, text.clone(), &mut context)?;
+        let mut context = std_context(path.clone(), None)?;
+        context.vm = Some(Vm::new());
+        assert_eq!(run_context(&path, text, &mut context)?, res);
+        assert_eq!(res.0, Some(Value::Vector(vec![Value::Int(10), Value::Int(3)], Some(Type::Int))));
+        // a call cached at the top level isn't reused where a local definition shadows it
+        let text = String::from("(def-global @f $(x any) #(return 0)) (def-global-inline @callf $() #(return (f 1)))
+            (def-global @g $() #{ (def @f $(x int) #(return 1)) (return (callf)) }) (return [(callf) (g)])");
+        let mut context = std_context(path.clone(), None)?;
+        let res = run_context(&path, text.clone(), &mut context)?;
+        let mut context = std_context(path.clone(), None)?;
+        context.vm = Some(Vm::new());
+        assert_eq!(run_context(&path, text, &mut context)?, res);
+        assert_eq!(res.0, Some(Value::Vector(vec![Value::Int(0), Value::Int(1)], Some(Type::Int))));
+        Ok(())
+    }
+    #[test]
+    fn scan_error_pos() {
+        let path = String::from("<test>");
+        match scan_file(&path, String::from("(print 1)\n(print \"abc)")) {
+            Err(Error::UnclosedString(pos)) => assert_eq!(pos.ln.start, 1),  // points at the second line
+            res => panic!("expected an unclosed string error, got {res:?}")
+        }
+    }
+    #[test]
+    fn snippet() {
+        let path = String::from("<test>");
+        let sources = HashMap::from([(path.clone(), String::from("(let @x 1)\n(set @x 2)"))]);
+        let pos = Position::new(1..2, 5..7, &path);
+        assert_eq!(print_snippet(&pos, &sources, false), " --> <test>:2:6\n  |\n2 | (set @x 2)\n  |      ^^\n");
+        // a tab takes four columns in the line and in the underline
+        let sources = HashMap::from([(path.clone(), String::from("\t(set @x 2)"))]);
+        let pos = Position::new(0..1, 6..8, &path);
+        assert_eq!(print_snippet(&pos, &sources, false), " --> <test>:1:7\n  |\n1 |     (set @x 2)\n  |          ^^\n");
+    }
+    #[test]
+    fn try_catch() -> Result<(), Error> {
+        let path = String::from("<test>");
+        let text = String::from("(mut @s \"\") (try #(error! \"bad\") @e #(set @s (e @msg)) #(set @s (+ s \"!\"))) (return s)");
+        let mut context = std_context(path.clone(), None)?;
+        assert_eq!(run_context(&path, text, &mut context)?.0, Some(Value::String("bad!".to_string())));
+        assert_eq!(context.scopes.len(), 1);  // scopes left by the error are removed
+        Ok(())
+    }
+    #[test]
+    fn std_embedded() -> Result<(), Error> {
+        let path = String::from("<test>");
+        let mut context = std_context_embedded(path.clone())?;
+        assert_eq!(run_context(&path, String::from("(return (sum [1 2 3]))"), &mut context)?.0, Some(Value::Int(6)));
+        Ok(())
+    }
+    #[test]
+    fn engine() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.set_global("base", Value::Int(10))?;
+        engine.eval_str("(def-global @add $(x int) #(return (+ base x)))")?;
+        assert_eq!(engine.call_function("add", vec![Value::Int(5)])?, Some(Value::Int(15)));
+        assert!(engine.eval_str("(add \"a\")").is_err());
+        assert_eq!(engine.eval_str("(sum [base 1])")?, Some(Value::Int(11)));  // still usable after an error
+        assert_eq!(engine.get_global("base"), Some(&Value::Int(10)));
+        // a script can't end the host process
+        assert!(matches!(engine.eval_str("(exit 3)"), Err(Error::Exit(3, _))));
+        engine.eval_str("(let @local 1)")?;
+        assert_eq!(engine.get_global("local"), None);
+        Ok(())
+    }
+    #[test]
+    fn env_set() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(env-set \"NODY_TEST_ENV_SET\" \"1\")")?;
+        assert_eq!(engine.eval_str("(env \"NODY_TEST_ENV_SET\")")?, Some(Value::String("1".to_string())));
+        // names the os rejects are errors instead of a panic
+        assert!(matches!(engine.eval_str("(env-set \"\" \"1\")"), Err(Error::Error(..))));
+        assert!(matches!(engine.eval_str("(env-set \"A=B\" \"1\")"), Err(Error::Error(..))));
+        // an unset variable is `()`, which can be passed on and compared
+        assert_eq!(engine.eval_str("(= (env \"NODY_TEST_ENV_UNSET\") ())")?, Some(Value::Bool(true)));
+        Ok(())
+    }
+    #[test]
+    fn set_undefined() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        assert!(matches!(engine.eval_str("(set (path @nope @x) 1)"), Err(Error::NotDefinedPath(..))));
+        assert!(matches!(engine.eval_str("(set (index @nope 0) 3)"), Err(Error::NotDefinedIndex(..))));
+        Ok(())
+    }
+    #[test]
+    fn native_closure() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        let count = Rc::new(RefCell::new(0));
+        let counter = count.clone();
+        engine.create_native_fn("count!", NativFunction::new(vec![("n".to_string(), Type::Int, false)], None, move |context| {
+            if let Some(Value::Int(n)) = context.get_var(&"n".to_string()) { *counter.borrow_mut() += n }
+            Ok((None, Return::None))
+        }, false))?;
+        engine.eval_str("(for @i 4 #(count! i))")?;
+        assert_eq!(*count.borrow(), 6);
+        Ok(())
+    }
+    #[test]
+    fn closures() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(def-global @make-counter $() #{ (mut @n 0) (return #{ (set @n (+ n 1)) (return n) }) })")?;
+        let res = engine.eval_str("(let @counter (make-counter)) (do counter) (return (do counter))")?;
+        assert_eq!(res, Some(Value::Int(2)));   // the closure keeps its own n between calls
+        engine.eval_str("(def-global @twice $(f closure) #{ (do f) (do f) })")?;
+        let res = engine.eval_str("(mut @hits 0) (twice #(set @hits (+ hits 1))) (return hits)")?;
+        assert_eq!(res, Some(Value::Int(2)));   // callbacks change the variables of the frame that created them
+        // a local function can call itself
+        engine.eval_str("(def-global @down $(n int) #{ (def @f $(n int) #(if (> n 0) #(return (+ 1 (f (- n 1)))) #(return 0))) (return (f n)) })")?;
+        assert_eq!(engine.eval_str("(down 4)")?, Some(Value::Int(4)));
+        // closures of one frame share its variables after it returned
+        engine.eval_str("(def-global @make $() #{ (mut @n 0) (let @inc #(set @n (+ n 1))) (let @get #(return n)) (set @n 10) (return [inc get]) })")?;
+        let res = engine.eval_str("(let @fs (make)) (do (fs 0)) (do (fs 0)) (return (do (fs 1)))")?;
+        assert_eq!(res, Some(Value::Int(12)));
+        Ok(())
+    }
+    #[test]
+    fn fn_values() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(def-global @adder $(n int) #(return (fn $(x int) #(+ x n) int)))")?;
+        engine.eval_str("(def-global @apply $(f fn x int) #(return (f x)))")?;
+        assert_eq!(engine.eval_str("(apply (adder 5) 10)")?, Some(Value::Int(15)));
+        assert_eq!(engine.eval_str("(let @fs [(adder 1) (adder 2)]) (return ((fs 1) 1))")?, Some(Value::Int(3)));
+        assert_eq!(engine.eval_str("(let @o ${f (adder 3)}) (return ((o @f) 1))")?, Some(Value::Int(4)));
+        assert_eq!(engine.eval_str("(type (adder 1))")?, Some(Value::Type(Type::Function(Some(vec![Type::Int]), Some(Box::new(Type::Int))))));
+        Ok(())
+    }
+    #[test]
+    fn overloads() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(def-global @f $(x any) #(return 0)) (def-global @f $(x int) #(return 1)) (def-global @f $(x int*) #(return 2))")?;
+        assert_eq!(engine.eval_str("(f 1)")?, Some(Value::Int(1)));     // exact over variadic and any, whatever the order
+        assert_eq!(engine.eval_str("(f 1 2)")?, Some(Value::Int(2)));
+        assert_eq!(engine.eval_str("(f 1.5)")?, Some(Value::Int(0)));
+        engine.eval_str("(def-global @g $(x int y any) #(return 0)) (def-global @g $(x any y int) #(return 1))")?;
+        assert!(matches!(engine.eval_str("(g 1 2)"), Err(Error::AmbiguousCall(..))));
+        // a local definition shadows a global one with the same params
+        assert_eq!(engine.eval_str("(def @f $(x int) #(return 3)) (return (f 1))")?, Some(Value::Int(3)));
+        // and a user definition shadows a native one
+        engine.eval_str("(def-global @len $(v str) #(return 0))")?;
+        assert_eq!(engine.eval_str("(len \"abc\")")?, Some(Value::Int(0)));
+        Ok(())
+    }
+    #[test]
+    fn return_types() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(def-global @f $(x any) #(return x) int) (def-global @g $() #{ (print \"g\") } int)")?;
+        assert_eq!(engine.eval_str("(f 1)")?, Some(Value::Int(1)));
+        assert!(matches!(engine.eval_str("(f \"x\")"), Err(Error::ExpectedType(Type::Int, Type::String, _))));
+        assert!(matches!(engine.eval_str("(g)"), Err(Error::MissingReturn(Type::Int, _))));
+        Ok(())
+    }
+    #[test]
+    fn unions() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(def-global @f $(n int|float) #(return 0)) (def-global @f $(n int) #(return 1))")?;
+        assert_eq!(engine.eval_str("(f 1)")?, Some(Value::Int(1)));     // an exact type is more specific than a union
+        assert_eq!(engine.eval_str("(f 1.5)")?, Some(Value::Int(0)));
+        assert!(engine.eval_str("(f \"x\")").is_err());
+        engine.eval_str("(def-global @g $(s str|none) #(return (type s)))")?;
+        assert_eq!(engine.eval_str("(g ())")?, Some(Value::Type(Type::None)));
+        assert_eq!(engine.eval_str("(type (fn $(n (int|float)) #n))")?.map(|typ| typ.to_string()), Some("fn(int|float)".to_string()));
+        // the std keeps the number type, also for empty vectors, and rejects mixed numbers at the call
+        assert_eq!(engine.eval_str("(sum 1.5 2.5)")?, Some(Value::Float(4.0)));
+        assert_eq!(engine.eval_str("(sum [1 2])")?, Some(Value::Int(3)));
+        assert_eq!(engine.eval_str("(sum (vec float []))")?, Some(Value::Float(0.0)));
+        assert_eq!(engine.eval_str("(min (vec float []))")?, Some(Value::Float(0.0)));
+        assert_eq!(engine.eval_str("(max (vec int []))")?, Some(Value::Int(0)));
+        assert!(matches!(engine.eval_str("(sum 1 2.5)"), Err(Error::FunctionPatternNotFound(..))));
+        Ok(())
+    }
+    #[test]
+    fn structs() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(struct @Point $(x float y float))")?;
+        engine.eval_str("(def-global @f $(p Point) #(return (p @y))) (def-global @f $(o obj) #(return 0.0))")?;
+        assert_eq!(engine.eval_str("(f (Point 1.0 2.0))")?, Some(Value::Float(2.0)));
+        assert_eq!(engine.eval_str("(f ${x 1.0})")?, Some(Value::Float(0.0)));
+        assert_eq!(engine.eval_str("(type (Point 1.0 2.0))")?.map(|typ| typ.to_string()), Some("Point".to_string()));
+        assert_eq!(engine.eval_str("(str (Point 1.5 2.5))")?, Some(Value::String("Point { x=1.5 y=2.5 }".to_string())));
+        let res = engine.eval_str("(mut @p (Point 1.0 2.0)) (set (path @p @x) 3.0) (return (p @x))")?;
+        assert_eq!(res, Some(Value::Float(3.0)));
+        assert!(matches!(engine.eval_str("(mut @p (Point 1.0 2.0)) (set (path @p @x) 3)"), Err(Error::ExpectedType(Type::Float, Type::Int, _))));
+        // declared types work in unions and vectors too
+        engine.eval_str("(def-global @g $(p Point|none) #(return (type p))) (def-global @n $(ps vec<Point>) #(return (len ps)))")?;
+        assert_eq!(engine.eval_str("(g ())")?, Some(Value::Type(Type::None)));
+        assert_eq!(engine.eval_str("(n [(Point 1.0 2.0)])")?, Some(Value::Int(1)));
+        assert!(matches!(engine.eval_str("(Point 1.0 2)"), Err(Error::ExpectedFieldType(field, Type::Float, Type::Int, _)) if field == "y"));
+        // a struct is passed as an object, but the types are still different
+        let (point, obj) = (Type::Struct("Point".to_string()), Type::Object);
+        assert!(point.matches(&obj) && !obj.matches(&point));
+        assert_ne!(point, obj);
+        assert_ne!(obj, point);
+        Ok(())
+    }
+    #[test]
+    fn enums() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(enum @Shape #${Circle $(r float) Rect $(w float h float)})")?;
+        engine.eval_str("(def-global @area $(s Shape) #(match s Circle #(return (* 3.0 r r)) Rect #(return (* w h))))")?;
+        engine.eval_str("(def-global @name $(s Circle) #(return \"circle\")) (def-global @name $(s Shape) #(return \"shape\"))")?;
+        assert_eq!(engine.eval_str("(area (Rect 2.0 3.0))")?, Some(Value::Float(6.0)));
+        assert_eq!(engine.eval_str("(name (Circle 1.0))")?, Some(Value::String("circle".to_string())));
+        assert_eq!(engine.eval_str("(name (Rect 1.0 1.0))")?, Some(Value::String("shape".to_string())));
+        assert!(matches!(engine.eval_str("(match (Rect 1.0 1.0) Circle #(return 0))"), Err(Error::NoMatch(..))));
+        engine.eval_str("(enum @Opt #${Some $(v int) Nothing $()})")?;
+        assert_eq!(engine.eval_str("(str (Nothing))")?, Some(Value::String("Nothing".to_string())));
+        Ok(())
+    }
+    #[test]
+    fn patterns() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(def-global @describe $(x any) #(match x 0 #(return \"zero\") #(int n) @if #(> n 9) #(return \"big\") int #(return \"int\") vec<str> #(return \"strs\") #[a _ rest*] #(return (len rest)) #${x int} #(return x) #_ #(return \"other\")))")?;
+        assert_eq!(engine.eval_str("(describe 0)")?, Some(Value::String("zero".to_string())));
+        assert_eq!(engine.eval_str("(describe 10)")?, Some(Value::String("big".to_string())));
+        assert_eq!(engine.eval_str("(describe 3)")?, Some(Value::String("int".to_string())));
+        assert_eq!(engine.eval_str("(describe [\"a\"])")?, Some(Value::String("strs".to_string())));
+        assert_eq!(engine.eval_str("(describe [1 2 3 4])")?, Some(Value::Int(2)));
+        assert_eq!(engine.eval_str("(describe ${x 5 y 6})")?, Some(Value::Int(5)));
+        assert_eq!(engine.eval_str("(describe [1])")?, Some(Value::String("other".to_string())));
+        // the bound names only live in the arm
+        assert_eq!(engine.eval_str("(let @a 1) (match [2 3] #[a b] #(+ a b)) (return a)")?, Some(Value::Int(1)));
+        engine.eval_str("(struct @Point $(x int y int))")?;
+        assert_eq!(engine.eval_str("(match (Point 1 2) #(Point a b) #(return (+ a b)))")?, Some(Value::Int(3)));
+        Ok(())
+    }
+    #[test]
+    fn continue_labels() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        assert_eq!(engine.eval_str("(mut @s 0) (for @i 10 #(if (= (% i 2) 0) #(continue) #(set @s (+ s i)))) (return s)")?, Some(Value::Int(25)));
+        assert_eq!(engine.eval_str("(mut @n 0) (while #(< n 3) #{ (set @n (+ n 1)) (continue) (set @n 10) }) (return n)")?, Some(Value::Int(3)));
+        assert_eq!(engine.eval_str("(mut @s 0) (for @outer @i 0 4 #(for @j 4 #{ (if (= j i) #(continue @outer)) (if (= i 3) #(break @outer)) (set @s (+ s 1)) })) (return s)")?, Some(Value::Int(3)));
+        assert_eq!(engine.eval_str("(mut @n 0) (loop @l #{ (set @n (+ n 1)) (for @x [1 2] #(break @l)) }) (return n)")?, Some(Value::Int(1)));
+        assert!(matches!(engine.eval_str("(loop #(break @nope))"), Err(Error::UnknownLabel(..))));
+        Ok(())
+    }
+    #[test]
+    fn iterators() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        assert_eq!(engine.eval_str("(mut @s \"\") (for @c \"abc\" #(set @s (+ (str c) s))) (return s)")?, Some(Value::String("cba".to_string())));
+        assert_eq!(engine.eval_str("(mut @s 0) (for @e ${a 1 b 2} #(set @s (+ s (e 1)))) (return s)")?, Some(Value::Int(3)));
+        assert_eq!(engine.eval_str("(collect (range 10 0 (- 4)))")?, engine.eval_str("(return [10 6 2])")?);
+        // a huge range stays lazy
+        assert_eq!(engine.eval_str("(collect (take 3 (filter (fn $(x int) #(return (= (% x 3) 0))) (map (fn $(x int) #(return (+ x 1))) (range 0 1000000000000)))))")?, engine.eval_str("(return [3 6 9])")?);
+        engine.eval_str("(let-global @pairs (collect (enumerate (zip \"ab\" [true false]))))")?;
+        assert_eq!(engine.eval_str("(len pairs)")?, Some(Value::Int(2)));
+        assert_eq!(engine.eval_str("(((pairs 1) 1) 0)")?, Some(Value::Char('b')));
+        assert_eq!(engine.eval_str("((pairs 1) 0)")?, Some(Value::Int(1)));
+        engine.eval_str("(def-global @countdown $(n int) #{ (mut @n n) (return ${next (fn $() #{ (if (= n 0) #(return ())) (set @n (- n 1)) (return n) })}) })")?;
+        assert_eq!(engine.eval_str("(collect (countdown 3))")?, engine.eval_str("(return [2 1 0])")?);
+        Ok(())
+    }
+    #[test]
+    fn generators() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(def-global @count $(to int) #{ (mut @i 0) (while #(< i to) #{ (yield i) (set @i (+ i 1)) }) })")?;
+        assert_eq!(engine.eval_str("(collect (count 3))")?, engine.eval_str("(return [0 1 2])")?);
+        assert_eq!(engine.eval_str("(mut @s 0) (for @x (count 5) #(set @s (+ s x))) (return s)")?, Some(Value::Int(10)));
+        // an endless generator only runs as far as it is taken
+        engine.eval_str("(def-global @squares $() #{ (mut @n 1) (loop #{ (yield (* n n)) (set @n (+ n 1)) }) })")?;
+        assert_eq!(engine.eval_str("(collect (take 3 (squares)))")?, engine.eval_str("(return [1 4 9])")?);
+        engine.eval_str("(def-global @flat $(xs vec) #(for @x xs #(match x (vec any) #(for @y (flat x) #(yield y)) #_ #(yield x))))")?;
+        assert_eq!(engine.eval_str("(collect (flat [[1 2] [] [3]]))")?, engine.eval_str("(return [1 2 3])")?);
+        engine.eval_str("(def-global @bad $() #(print (yield 1)))")?;
+        assert!(matches!(engine.eval_str("(collect (bad))"), Err(Error::YieldInArgument(..))));
+        // a generator that yields in a handler continues there with the error still bound
+        engine.eval_str("(def-global @caught $() #(try #{ (yield 1) (error! \"boom\") } @e #{ (yield 99) (yield (len (e @msg))) }))")?;
+        assert_eq!(engine.eval_str("(collect (caught))")?, engine.eval_str("(return [1 99 4])")?);
+        // there is nothing to suspend outside of a generator
+        assert!(matches!(engine.eval_str("(yield 1)"), Err(Error::YieldOutsideGenerator(..))));
+        assert!(matches!(engine.eval_str("(for @x [1 2] #(yield x))"), Err(Error::YieldOutsideGenerator(..))));
+        Ok(())
+    }
+    #[test]
+    fn strings() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(let @s \"héllo wörld\")")?;
+        assert_eq!(engine.eval_str("(len s)")?, Some(Value::Int(11)));
+        assert_eq!(engine.eval_str("(s 1)")?, Some(Value::Char('é')));
+        assert_eq!(engine.eval_str("(find s \"wö\")")?, Some(Value::Int(6)));
+        assert_eq!(engine.eval_str("(find s 'z')")?, Some(Value::None));
+        assert_eq!(engine.eval_str("(split s)")?, engine.eval_str("(return [\"héllo\" \"wörld\"])")?);
+        assert_eq!(engine.eval_str("(upper (replace s \"ö\" \"o\"))")?, Some(Value::String("HÉLLO WORLD".to_string())));
+        assert_eq!(engine.eval_str("(pad-left (trim \" ñ \") 3 '.')")?, Some(Value::String("..ñ".to_string())));
+        assert_eq!(engine.eval_str("(chars \"añ\")")?, engine.eval_str("(return ['a' 'ñ'])")?);
+        assert_eq!(engine.eval_str("(starts-with? s \"hé\")")?, Some(Value::Bool(true)));
+        Ok(())
+    }
+    #[test]
+    fn modules() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.eval_str("(import \"samples/geom.nd\" @geom)")?;
+        assert_eq!(engine.eval_str("(geom.area 3 4)")?, Some(Value::Int(12)));
+        // private helpers are still there for the functions of the module
+        assert_eq!(engine.eval_str("(geom.area 5)")?, Some(Value::Int(25)));
+        assert_eq!(engine.eval_str("(geom.cube 3)")?, Some(Value::Int(27)));   // inline ones too
+        assert_eq!(engine.eval_str("(geom.dist2 geom.origin (geom.Point 1 2))")?, Some(Value::Int(5)));
+        assert!(matches!(engine.eval_str("(geom.square 2)"), Err(Error::NotDefined(..))));
+        // nothing of the module leaks into the global scope
+        assert!(matches!(engine.eval_str("(area 1 1)"), Err(Error::NotDefined(..))));
+        // a dotted name sets the member of a mutable object
+        assert_eq!(engine.eval_str("(mut @o ${x 1}) (set @o.x 2) (return o.x)")?, Some(Value::Int(2)));
+        assert!(matches!(engine.eval_str("(let @p ${x 1}) (set @p.x 2)"), Err(Error::Immutable(..))));
+        assert!(matches!(engine.eval_str("(mut @q ${x 1}) (set @q.y 2)"), Err(Error::NotDefined(..))));
+        Ok(())
+    }
+    #[test]
+    fn module_registry() -> Result<(), Error> {
+        let dir = std::env::temp_dir().join(format!("nody-modules-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("once.nd"), "(let-global @loaded true)").unwrap();
+        fs::write(dir.join("a.nd"), "(import \"b.nd\" @b)").unwrap();
+        fs::write(dir.join("b.nd"), "(import \"a.nd\" @a)").unwrap();
+        let mut engine = Engine::new()?;
+        // a second import of the same file doesn't run it again
+        let once = dir.join("once.nd").display().to_string();
+        engine.eval_str(&format!("(import {once:?}) (import {once:?})"))?;
+        // `b.nd` is found next to `a.nd`, which imports it
+        let a = dir.join("a.nd").display().to_string();
+        match engine.eval_str(&format!("(import {a:?} @a)")) {
+            Err(Error::ImportCycle(chain, _)) => assert_eq!(chain.len(), 3),
+            res => panic!("expected an import cycle, got {res:?}")
+        }
+        fs::remove_dir_all(&dir).unwrap();
+        Ok(())
+    }
+    #[test]
+    fn native_fn_typed() -> Result<(), Error> {
+        let mut engine = Engine::new()?;
+        engine.register_fn("hyp", |a: f64, b: f64| (a * a + b * b).sqrt())?;
+        engine.register_fn("sum", |n: i64, nums: Rest<i64>| nums.0.iter().fold(n, |sum, n| sum + n))?;
+        engine.register_fn("words", |s: String| s.split(' ').map(String::from).collect::<Vec<String>>())?;
+        assert_eq!(engine.eval_str("(hyp 3.0 4.0)")?, Some(Value::Float(5.0)));
+        assert_eq!(engine.eval_str("(sum 1 2 3)")?, Some(Value::Int(6)));
+        assert_eq!(engine.eval_str("(len (words \"a b c\"))")?, Some(Value::Int(3)));
+        // a mismatching argument is a type error instead of a panic
+        let mut context = Context::new("<test>".to_string(), None);
+        context.create_var("x".to_string(), Value::Int(1), false, Position::new(0..0, 0..0, &"<test>".to_string()), false)?;
+        assert!(matches!(context.arg::<String>("x"), Err(Error::ExpectedType(Type::String, Type::Int, _))));
+        Ok(())
+    }
+}
@@ -1,99 +1,120 @@
-#![allow(unused)]
-pub mod errors;
-pub mod value;
-pub mod context;
-pub mod scan;
-pub mod interpret;
-pub mod nody_std;
-use errors::*;
-use value::*;
-use context::*;
-use scan::*;
-use interpret::*;
-use nody_std::*;
-use std::slice::Iter;
-use std::ops::{Range};
-use std::collections::HashMap;
-use std::fmt::{Debug, Display};
-use core::num::IntErrorKind;
-use std::cmp::{min, max};
+#![allow(clippy::result_large_err)] // errors carry their position and values
+use nody::*;
 use std::io::{Write, stdin, stdout};
 use std::{thread, fs, env};
 
 const STACK_SIZE: usize = 4 * 1024 * 1024;
 
-pub fn run(path: &String, text: String, std_path: Option<String>) -> Result<(Option<Value>, Return), Error> {
-    let mut context = std_context(path.clone(), std_path)?;
-    interpret(&scan_file(path, text)?, &mut context)
+// exposes the arguments after the script as the `args` global
+pub fn set_args<'a>(engine: &mut Engine, args: impl Iterator<Item = &'a String>) -> Result<(), Error> {
+    let values: Vec<Value> = args.map(|arg| Value::String(arg.clone())).collect();
+    engine.set_global("args", Value::Vector(values, Some(Type::String)))
 }
-pub fn run_context(path: &String, text: String, context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    interpret(&scan_file(path, text)?, context)
-}
-pub fn run_file(path: &String, std_path: Option<String>) -> Result<(Option<Value>, Return), Error> {
-    match std::fs::read_to_string(path) {
-        Ok(text) => run(path, text, std_path),
-        Err(_) => Err(Error::TargetFileNotFound(path.clone()))
+// runs the main script or code and returns the exit code of the process
+pub fn run_script(path: &String, text: Option<String>, engine: &mut Engine) -> i32 {
+    let text = match text.map_or_else(|| fs::read_to_string(path), Ok) {
+        Ok(text) => text,
+        Err(_) => {
+            let e = Error::TargetFileNotFound(path.clone(), Position::new(0..0, 0..0, path));
+            eprintln!("{}", engine.print_error(&e));
+            return e.exit_code()
+        }
+    };
+    let node = match engine.scan(path, text) {
+        Ok(node) => node,
+        Err(e) => {
+            eprintln!("{}", engine.print_error(&e));
+            return e.exit_code()
+        }
+    };
+    match engine.run(&node) {
+        Ok(value) => {
+            if let Some(value) = value { println!("{value}") }
+            0
+        }
+        Err(Error::Exit(code, _)) => code,
+        Err(e) => {
+            eprintln!("{}", engine.print_error(&e));
+            e.exit_code()
+        }
     }
 }
-pub fn run_file_context(path: &String, context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    match std::fs::read_to_string(path) {
-        Ok(text) => run_context(path, text, context),
-        Err(_) => Err(Error::TargetFileNotFound(path.clone()))
+// the context with the std of `--std`, `NODY_STD` or next to the executable, a dir that was given has to have one
+pub fn load_std(no_std: bool, std_flag: Option<String>) -> Result<Context, Error> {
+    if no_std { return std_context("<STD>".to_string(), None) }
+    match find_std_dir(std_flag) {
+        Some(dir) => std_context("<STD>".to_string(), Some(dir)),
+        None => std_context_embedded("<STD>".to_string())
     }
 }
-
-pub fn nody() {
+pub fn nody() -> i32 {
     let args: Vec<String> = env::args().collect();
-    let mut args = args.iter();
+    let mut args = args.iter().peekable();
     args.next();
-    let std_path = if let Ok(path) = env::current_exe() {
-        let path = path.display().to_string();
-        let path_split = path.split("\\").collect::<Vec<&str>>();
-        let mut path: Vec<String> = vec![];
-        for x in path_split.get(0..path_split.len()-1).unwrap() {
-            path.push(x.to_string());
+    let (mut vm, mut no_std, mut std_flag) = (false, false, None);
+    while let Some(flag) = args.next_if(|arg| ["--vm", "--std", "--no-std"].contains(&arg.as_str())) {
+        match flag.as_str() {
+            "--vm" => vm = true,
+            "--no-std" => no_std = true,
+            _ => std_flag = args.next().cloned()
         }
-        path.push("nody_std".to_string());
-        Some(path.join("\\"))
-    } else { None };
-    let mut context = std_context("<STD>".to_string(), std_path.clone()).unwrap_or_else(|e| {
-        eprintln!("{e}");
-        Context::new("<STD>".to_string(), std_path)
-    });
+    }
+    let mut context = match load_std(no_std, std_flag) {
+        Ok(context) => context,
+        Err(e) => {
+            eprintln!("{e}");
+            return e.exit_code()
+        }
+    };
+    if vm { context.vm = Some(Vm::new()) }
+    let mut engine = Engine::from_context(context);
     match args.next() {
         Some(path) => match path.as_str() {
             "-i" | "-interpret" => if let Some(text) = args.next() {
-                match run_context(&"<stdin>".to_string(), text.clone(), &mut context) {
-                    Ok((value, ret)) => if let Some(value) = value { println!("{value}") }
-                    Err(e) => println!("{e}\n{}", print_trace(&context.trace)) 
+                if let Err(e) = set_args(&mut engine, args) {
+                    eprintln!("{}", engine.print_error(&e));
+                    return e.exit_code()
                 }
+                return run_script(&"<stdin>".to_string(), Some(text.clone()), &mut engine)
             }
             "-h" | "-help" => {
                 println!("USAGE:");
                 println!("  nody                        -> opens the shell");
-                println!("  nody [file path]            -> execute file");
+                println!("  nody [file path] [args]     -> execute file with the args global");
                 println!("  nody -h/-help               -> prints out this usage page");
                 println!("  nody -i/-interpret [code]   -> execute code");
+                println!("  nody --vm ...               -> execute with the bytecode vm");
+                println!("  nody --std [dir] ...        -> load std.nd from the directory");
+                println!("  nody --no-std ...           -> start with only the native functions");
             }
-            _ => match run_file_context(path, &mut context) {
-                Ok((value, ret)) => if let Some(value) = value { println!("{value}") }
-                Err(e) => println!("{e}\n{}", print_trace(&context.trace))
+            _ => {
+                if let Err(e) = set_args(&mut engine, args) {
+                    eprintln!("{}", engine.print_error(&e));
+                    return e.exit_code()
+                }
+                return run_script(path, None, &mut engine)
             }
         }
         None => {
+            if let Err(e) = set_args(&mut engine, args) {
+                eprintln!("{}", engine.print_error(&e));
+                return e.exit_code()
+            }
             println!("This is the Nody shell");
             loop {
                 let mut input = String::new();
                 print!("> ");
-                stdout().flush();
-                stdin().read_line(&mut input);
-                match run_context(&"<stdin>".to_string(), input, &mut context) {
-                    Ok((value, ret)) => if let Some(value) = value { println!("{value}") }
-                    Err(e) => println!("{e}\n{}", print_trace(&context.trace))
+                let _ = stdout().flush();
+                if let Ok(0) = stdin().read_line(&mut input) { return 0 } // end of input
+                match engine.scan("<stdin>", input).and_then(|node| engine.run(&node)) {
+                    Ok(value) => if let Some(value) = value { println!("{value}") }
+                    Err(Error::Exit(code, _)) => return code,
+                    Err(e) => eprintln!("{}", engine.print_error(&e))
                 }
             }
         }
     }
+    0
 }
 
 fn main() {
@@ -102,7 +123,9 @@ fn main() {
         .stack_size(STACK_SIZE)
         .spawn(nody)
         .unwrap();
-    program.join().unwrap();
+    let code = program.join().unwrap();
+    let _ = stdout().flush();
+    std::process::exit(code);
 }
 
 #[cfg(test)]
@@ -110,31 +133,36 @@ mod test {
     use super::*;
 
     #[test]
-    fn context() {
-        let mut context = Context::new("".to_string(), Some("".to_string()));
-        assert_eq!(context.scopes.len(), 1); // first scope exists
-        context.push();                      // second scope
-        assert_eq!(context.scopes.len(), 2); // 2 scopes
-        let scope = context.pop();           // pop second scope
-        assert!(scope.is_some());            // is a scope
-        assert_eq!(context.scopes.len(), 1); // 1 scope
+    fn exit_codes() -> Result<(), Error> {
+        let path = String::from("<test>");
+        let mut engine = Engine::from_context(std_context(path.clone(), None)?);
+        assert_eq!(run_script(&"<missing>.nd".to_string(), None, &mut engine), EXIT_FILE_NOT_FOUND);
+        assert_eq!(run_script(&path, Some(String::from("(print \"abc")), &mut engine), EXIT_SCAN);
+        assert_eq!(run_script(&path, Some(String::from("(+ 1 \"a\")")), &mut engine), EXIT_RUNTIME);
+        assert_eq!(run_script(&path, Some(String::from("(error! \"custom\" 42)")), &mut engine), 42);
+        assert_eq!(run_script(&path, Some(String::from("(try #(exit 7) #(print \"caught\"))")), &mut engine), 7);
+        // a failing script never exits with 0 or a code the os would cut off
+        assert_eq!(run_script(&path, Some(String::from("(error! \"custom\" 0)")), &mut engine), EXIT_RUNTIME);
+        assert_eq!(run_script(&path, Some(String::from("(error! \"custom\" 256)")), &mut engine), EXIT_RUNTIME);
+        // errors of imported files have the code of their kind
+        assert_eq!(run_script(&path, Some(String::from("(import \"<missing>.nd\")")), &mut engine), EXIT_FILE_NOT_FOUND);
+        let broken = env::temp_dir().join(format!("nody-broken-{}.nd", std::process::id()));
+        fs::write(&broken, "(print \"abc").unwrap();
+        assert_eq!(run_script(&path, Some(format!("(import {:?})", broken.display().to_string())), &mut engine), EXIT_SCAN);
+        fs::remove_file(&broken).unwrap();
+        // a std dir that was asked for isn't replaced by another one
+        assert!(matches!(load_std(false, Some("<missing>".to_string())), Err(e) if e.exit_code() == EXIT_FILE_NOT_FOUND));
+        Ok(())
     }
     #[test]
-    fn context_vars() -> Result<(), Error> {
-        let mut context = Context::new("".to_string(), Some("".to_string()));
+    fn exit() -> Result<(), Error> {
         let path = String::from("<test>");
-        let pos = Position::new(0..0, 0..0, &path);
-        let x = String::from("x");
-        context.create_var(x.clone(), Value::Int(1), false, pos.clone(), false)?;     // x definition in first scope
-        assert_eq!(context.get_var(&x), Some(&Value::Int(1)));                        // x accessable correctly
-        context.push();                                                               // second scope
-        assert_eq!(context.get_var(&x), Some(&Value::Int(1)));                        // x still accessable
-        let y = String::from("y"); 
-        context.create_var(y.clone(), Value::Bool(true), false, pos.clone(), false)?; // y definition in second scope
-        assert_eq!(context.get_var(&y), Some(&Value::Bool(true)));                    // y accessable correctly
-        assert_eq!(context.get_var(&x), Some(&Value::Int(1)));                        // x in first scope still accessable
-        context.pop();                                                                // delete second scope
-        assert_eq!(context.get_var(&y), None);                                        // y deleted
+        let mut engine = Engine::from_context(std_context(path.clone(), None)?);
+        assert_eq!(run_script(&path, Some(String::from("(exit 0)")), &mut engine), 0);
+        assert_eq!(run_script(&path, Some(String::from("(exit 255)")), &mut engine), 255);
+        // codes the os would cut off are an error instead
+        assert_eq!(run_script(&path, Some(String::from("(exit 256)")), &mut engine), EXIT_RUNTIME);
+        assert_eq!(run_script(&path, Some(String::from("(exit (- 0 1))")), &mut engine), EXIT_RUNTIME);
         Ok(())
     }
-}
\ No newline at end of file
+}
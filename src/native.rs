@@ -0,0 +1,144 @@
+use crate::*;
+
+// conversion of a nody value into a rust value, `typ` is the param type a native declares for it
+pub trait FromValue: Sized {
+    fn typ() -> Type;
+    fn variadic() -> bool { false }
+    fn from_value(value: Value) -> Option<Self>;
+}
+pub trait IntoValue {
+    fn typ() -> Type;
+    fn into_value(self) -> Value;
+}
+
+macro_rules! impl_value {
+    ($rust:ty, $variant:ident, $typ:expr) => {
+        impl FromValue for $rust {
+            fn typ() -> Type { $typ }
+            fn from_value(value: Value) -> Option<Self> {
+                if let Value::$variant(v) = value { Some(v) } else { None }
+            }
+        }
+        impl IntoValue for $rust {
+            fn typ() -> Type { $typ }
+            fn into_value(self) -> Value { Value::$variant(self) }
+        }
+    };
+}
+impl_value!(i64, Int, Type::Int);
+impl_value!(f64, Float, Type::Float);
+impl_value!(char, Char, Type::Char);
+impl_value!(bool, Bool, Type::Bool);
+impl_value!(String, String, Type::String);
+impl_value!(Path, Path, Type::Path);
+impl_value!(Index, Index, Type::Index);
+impl_value!(Closure, Closure, Type::Closure);
+impl_value!(Params, Params, Type::Params);
+impl_value!(Scope, Object, Type::Object);
+impl_value!(Type, Type, Type::Type);
+impl_value!(Function, Function, Type::Function(None, None));
+
+impl FromValue for Value {
+    fn typ() -> Type { Type::Any }
+    fn from_value(value: Value) -> Option<Self> { Some(value) }
+}
+impl IntoValue for Value {
+    fn typ() -> Type { Type::Any }
+    fn into_value(self) -> Value { self }
+}
+
+// an optional argument, `()` is none
+impl<T: FromValue> FromValue for Option<T> {
+    fn typ() -> Type { Type::Union(vec![T::typ(), Type::None]) }
+    fn from_value(value: Value) -> Option<Self> {
+        if let Value::None = value { Some(None) } else { T::from_value(value).map(Some) }
+    }
+}
+
+// a key like `@x`, kept apart from strings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Key(pub String);
+impl FromValue for Key {
+    fn typ() -> Type { Type::Key }
+    fn from_value(value: Value) -> Option<Self> {
+        if let Value::Key(v) = value { Some(Key(v)) } else { None }
+    }
+}
+impl IntoValue for Key {
+    fn typ() -> Type { Type::Key }
+    fn into_value(self) -> Value { Value::Key(self.0) }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn typ() -> Type { Type::Vector(Some(Box::new(T::typ()))) }
+    fn from_value(value: Value) -> Option<Self> {
+        if let Value::Vector(values, _) = value { values.into_iter().map(T::from_value).collect() } else { None }
+    }
+}
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn typ() -> Type { Type::Vector(Some(Box::new(T::typ()))) }
+    fn into_value(self) -> Value {
+        let values: Vec<Value> = self.into_iter().map(T::into_value).collect();
+        let typ = values.first().map(|value| value.typ()); // like a vector literal
+        Value::Vector(values, typ)
+    }
+}
+
+// the last param of a native that collects all remaining arguments of its type
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rest<T>(pub Vec<T>);
+impl<T: FromValue> FromValue for Rest<T> {
+    fn typ() -> Type { T::typ() }
+    fn variadic() -> bool { true }
+    fn from_value(value: Value) -> Option<Self> { Vec::<T>::from_value(value).map(Rest) }
+}
+
+// what a native returns, turned into the (value, return) pair of the interpreter
+pub trait IntoReturn {
+    fn return_type() -> Option<Type>;
+    fn into_return(self) -> Result<(Option<Value>, Return), Error>;
+}
+impl<T: IntoValue> IntoReturn for T {
+    fn return_type() -> Option<Type> { Some(T::typ()) }
+    fn into_return(self) -> Result<(Option<Value>, Return), Error> { Ok((Some(self.into_value()), Return::None)) }
+}
+impl IntoReturn for () {
+    fn return_type() -> Option<Type> { None }
+    fn into_return(self) -> Result<(Option<Value>, Return), Error> { Ok((None, Return::None)) }
+}
+impl<T: IntoValue> IntoReturn for Option<T> {
+    fn return_type() -> Option<Type> { Some(T::typ()) }
+    fn into_return(self) -> Result<(Option<Value>, Return), Error> { Ok((self.map(T::into_value), Return::None)) }
+}
+impl<T: IntoReturn> IntoReturn for Result<T, Error> {
+    fn return_type() -> Option<Type> { T::return_type() }
+    fn into_return(self) -> Result<(Option<Value>, Return), Error> { self?.into_return() }
+}
+
+// rust functions that can be registered as natives, the params are derived from the argument types
+pub trait IntoNative<Args> {
+    fn into_native(self) -> NativFunction;
+}
+macro_rules! impl_into_native {
+    ($($arg:ident $id:literal),*) => {
+        impl<F, R, $($arg),*> IntoNative<($($arg,)*)> for F
+        where F: Fn($($arg),*) -> R + 'static, R: IntoReturn, $($arg: FromValue),* {
+            fn into_native(self) -> NativFunction {
+                NativFunction::new(
+                    vec![$(($id.to_string(), $arg::typ(), $arg::variadic())),*],
+                    R::return_type(),
+                    #[allow(unused_variables)] // a native without params doesn't read its arguments
+                    move |context| self($(context.arg::<$arg>($id)?),*).into_return(),
+                    false
+                )
+            }
+        }
+    };
+}
+impl_into_native!();
+impl_into_native!(A "a");
+impl_into_native!(A "a", B "b");
+impl_into_native!(A "a", B "b", C "c");
+impl_into_native!(A "a", B "b", C "c", D "d");
+
+pub fn native_fn<Args>(f: impl IntoNative<Args>) -> NativFunction { f.into_native() }
@@ -2,1140 +2,1087 @@ use crate::*;
 
 // let
 fn _let(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":id".to_string()).unwrap().clone();
-    let v = context.get_var(&":v".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        let len = context.scopes.len();
-        match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
-            Some(scope) => scope.create_var(id, v, false, pos, false)?,
-            None => context.create_var(id, v, false, pos, false)?
-        }
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let v: Value = context.arg(":v")?;
+    let len = context.scopes.len();
+    match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
+        Some(scope) => scope.create_var(id, v, false, pos, false)?,
+        None => context.create_var(id, v, false, pos, false)?
+    }
+    Ok((None, Return::None))
 }
 fn _let_global(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":id".to_string()).unwrap().clone();
-    let v = context.get_var(&":v".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        context.global.create_var(id, v, false, pos, false)?;
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let v: Value = context.arg(":v")?;
+    context.global.create_var(id, v, false, pos, false)?;
+    Ok((None, Return::None))
 }
 fn _mut(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":id".to_string()).unwrap().clone();
-    let v = context.get_var(&":v".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        let len = context.scopes.len();
-        match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
-            Some(scope) => scope.create_var(id, v, true, pos, false)?,
-            None => context.create_var(id, v, true, pos, false)?
-        }
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let v: Value = context.arg(":v")?;
+    let len = context.scopes.len();
+    match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
+        Some(scope) => scope.create_var(id, v, true, pos, false)?,
+        None => context.create_var(id, v, true, pos, false)?
+    }
+    Ok((None, Return::None))
 }
 fn _mut_global(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":id".to_string()).unwrap().clone();
-    let v = context.get_var(&":v".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        context.global.create_var(id, v, true, pos, false)?;
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let v: Value = context.arg(":v")?;
+    context.global.create_var(id, v, true, pos, false)?;
+    Ok((None, Return::None))
 }
 fn _set(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let v = context.get_var(&":v".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if context.get_var(&id).is_none() { return Err(Error::NotDefined(id)) }
-        if !context.is_mutable(&id).unwrap() { return Err(Error::Immutable(id)) }
-        context.change(id, v)?;
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let v: Value = context.arg(":v")?;
+    match context.is_mutable(&id) {
+        None => return Err(Error::NotDefined(id, pos)),
+        Some(false) => return Err(Error::Immutable(id, pos)),
+        Some(true) => context.change(id, v, pos)?
+    }
+    Ok((None, Return::None))
 }
 fn _set_path(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&":v".to_string()).unwrap().clone();
-    context.trace_push(&context.get_var_pos(&":v".to_string()).unwrap().clone());
-    let mut path = context.get_var_mut(&":path".to_string()).unwrap().clone();
-    if let Value::Path(path) = &mut path {
-        let mutable = path.is_mutable(context)?.unwrap();
-        match path.get_mut(context)? {
-            Some(value) => if mutable {
-                if v.typ() != value.typ() { return Err(Error::ExpectedType(value.typ(), v.typ())) }
-                *value = v;
-                context.trace_pop();
-                Ok((None, Return::None))
-            } else {
-                Err(Error::ImmutablePath(path.clone()))
-            }
-            None => Err(Error::NotDefinedPath(path.clone()))
+    let v: Value = context.arg(":v")?;
+    let v_pos = context.arg_pos(":v");
+    context.trace_push(&v_pos);
+    let pos = context.arg_pos(":path");
+    let mut path: Path = context.arg(":path")?;
+    let Some(mutable) = path.is_mutable(context)? else { return Err(Error::NotDefinedPath(path, pos)) };
+    let field_type = path.field_type(context, &pos)?;
+    match path.get_mut(context, &pos)? {
+        Some(value) => if mutable {
+            let typ = field_type.unwrap_or_else(|| value.typ());
+            if !v.typ().matches(&typ) { return Err(Error::ExpectedType(typ, v.typ(), v_pos)) }
+            *value = v;
+            context.trace_pop();
+            Ok((None, Return::None))
+        } else {
+            Err(Error::ImmutablePath(path.clone(), pos))
         }
-    } else { panic!("type checking doesn't work") }
+        None => Err(Error::NotDefinedPath(path.clone(), pos))
+    }
 }
 fn _set_index(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&":v".to_string()).unwrap().clone();
-    context.trace_push(&context.get_var_pos(&":v".to_string()).unwrap().clone());
-    let mut index = context.get_var_mut(&":index".to_string()).unwrap().clone();
-    if let Value::Index(index) = &mut index {
-        let mutable = index.is_mutable(context)?.unwrap();
-        match index.get_mut(context)? {
-            Some(value) => if mutable {
-                if v.typ() != value.typ() {
-                    return Err(Error::ExpectedType(value.typ(), v.typ()))
-                }
-                *value = v;
-                context.trace_pop();
-                Ok((None, Return::None))
-            } else {
-                Err(Error::ImmutableIndex(index.clone()))
+    let v: Value = context.arg(":v")?;
+    let v_pos = context.arg_pos(":v");
+    context.trace_push(&v_pos);
+    let pos = context.arg_pos(":index");
+    let mut index: Index = context.arg(":index")?;
+    let Some(mutable) = index.is_mutable(context)? else { return Err(Error::NotDefinedIndex(index, pos)) };
+    match index.get_mut(context, &pos)? {
+        Some(value) => if mutable {
+            if v.typ() != value.typ() {
+                return Err(Error::ExpectedType(value.typ(), v.typ(), v_pos))
             }
-            None => Err(Error::NotDefinedIndex(index.clone()))
+            *value = v;
+            context.trace_pop();
+            Ok((None, Return::None))
+        } else {
+            Err(Error::ImmutableIndex(index.clone(), pos))
         }
-    } else { panic!("type checking doesn't work") }
+        None => Err(Error::NotDefinedIndex(index.clone(), pos))
+    }
 }
 fn _get(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        match context.get_var(&id) {
-            Some(value) => Ok((Some(value.clone()), Return::None)),
-            None => Err(Error::NotDefined(id))
-        }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    match context.get_var(&id) {
+        Some(value) => Ok((Some(value.clone()), Return::None)),
+        None => Err(Error::NotDefined(id, pos))
+    }
 }
 fn _get_path(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let path = context.get_var(&":path".to_string()).unwrap().clone();
-    if let Value::Path(path) = path {
-        match path.get(context)? {
-            Some(value) => Ok((Some(value.clone()), Return::None)),
-            None => Err(Error::NotDefinedPath(path))
-        }
-    } else { panic!("type checking doesn't work") }
+    let path: Path = context.arg(":path")?;
+    let pos = context.arg_pos(":path");
+    match path.get(context, &pos)? {
+        Some(value) => Ok((Some(value.clone()), Return::None)),
+        None => Err(Error::NotDefinedPath(path, pos))
+    }
 }
 fn _get_index(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let index = context.get_var(&":index".to_string()).unwrap().clone();
-    if let Value::Index(index) = index {
-        match index.get(context)? {
-            Some(value) => Ok((Some(value.clone()), Return::None)),
-            None => Err(Error::NotDefinedIndex(index))
-        }
-    } else { panic!("type checking doesn't work") }
+    let index: Index = context.arg(":index")?;
+    let pos = context.arg_pos(":index");
+    match index.get(context, &pos)? {
+        Some(value) => Ok((Some(value.clone()), Return::None)),
+        None => Err(Error::NotDefinedIndex(index, pos))
+    }
 }
 fn _exist(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap();
-    if let Value::Key(id) = id {
-        Ok((Some(Value::Bool(context.get_var(id).is_some())), Return::None))
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    Ok((Some(Value::Bool(context.get_var(&id).is_some())), Return::None))
 }
 fn _exist_path(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let path = context.get_var(&":path".to_string()).unwrap().clone();
-    if let Value::Path(path) = path {
-        Ok((Some(Value::Bool(match path.get(context) {
-            Ok(value) => value.is_some(),
-            Err(_) => false
-        })), Return::None))
-    } else { panic!("type checking doesn't work") }
+    let path: Path = context.arg(":path")?;
+    let pos = context.arg_pos(":path");
+    Ok((Some(Value::Bool(match path.get(context, &pos) {
+        Ok(value) => value.is_some(),
+        Err(_) => false
+    })), Return::None))
 }
 fn _exist_index(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let index = context.get_var(&":index".to_string()).unwrap().clone();
-    if let Value::Index(index) = index {
-        Ok((Some(Value::Bool(match index.get(context) {
-            Ok(value) => value.is_some(),
-            Err(_) => false
-        })), Return::None))
-    } else { panic!("type checking doesn't work") }
+    let index: Index = context.arg(":index")?;
+    let pos = context.arg_pos(":index");
+    Ok((Some(Value::Bool(match index.get(context, &pos) {
+        Ok(value) => value.is_some(),
+        Err(_) => false
+    })), Return::None))
 }
 fn _is_mut(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        match context.is_mutable(&id) {
-            Some(mutable) => Ok((Some(Value::Bool(mutable)), Return::None)),
-            None => Err(Error::NotDefined(id))
-        }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    match context.is_mutable(&id) {
+        Some(mutable) => Ok((Some(Value::Bool(mutable)), Return::None)),
+        None => Err(Error::NotDefined(id, pos))
+    }
 }
 fn _is_mut_path(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let path = context.get_var(&":path".to_string()).unwrap().clone();
-    if let Value::Path(path) = path {
-        match path.is_mutable(context)? {
-            Some(mutable) => Ok((Some(Value::Bool(mutable)), Return::None)),
-            None => Err(Error::NotDefinedPath(path))
-        }
-    } else { panic!("type checking doesn't work") }
+    let path: Path = context.arg(":path")?;
+    let pos = context.arg_pos(":path");
+    match path.is_mutable(context)? {
+        Some(mutable) => Ok((Some(Value::Bool(mutable)), Return::None)),
+        None => Err(Error::NotDefinedPath(path, pos))
+    }
 }
 fn _is_mut_index(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let index = context.get_var(&":index".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":index".to_string()).unwrap().clone();
-    if let Value::Index(index) = index {
-        match index.is_mutable(context)? {
-            Some(mutable) => Ok((Some(Value::Bool(mutable)), Return::None)),
-            None => Err(Error::NotDefinedIndex(index))
-        }
-    } else { panic!("type checking doesn't work") }
+    let index: Index = context.arg(":index")?;
+    let pos = context.arg_pos(":index");
+    match index.is_mutable(context)? {
+        Some(mutable) => Ok((Some(Value::Bool(mutable)), Return::None)),
+        None => Err(Error::NotDefinedIndex(index, pos))
+    }
 }
 // def
 fn _def(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let p = context.get_var(&":p".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":body".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if let Value::Params(p) = p {
-            if let Value::Closure(body) = body {
-                let func = Function::new(p, None, Box::new(body), false);
-                let len = context.scopes.len();
-                match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
-                    Some(scope) => scope.create_fn(id, func, pos)?,
-                    None => context.create_fn(id, func, pos)?
-                }
-                Ok((None, Return::None))
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let p: Params = context.arg(":p")?;
+    let body: Closure = context.arg(":body")?;
+    let pos = context.arg_pos(":body");
+    let func = Function::new(p, None, body.node, false).with_env(body.env).with_self(&id, &pos, context.frame_id());
+    let len = context.scopes.len();
+    match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
+        Some(scope) => scope.create_fn(id, func, pos)?,
+        None => context.create_fn(id, func, pos)?
+    }
+    Ok((None, Return::None))
 }
 fn _def_return(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let p = context.get_var(&":p".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    let return_type = context.get_var(&":return_type".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":body".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if let Value::Params(p) = p {
-            if let Value::Closure(body) = body {
-                if let Value::Type(return_type) = return_type {
-                    let func = Function::new(p, Some(return_type), Box::new(body), false);
-                    let len = context.scopes.len();
-                    if context.fn_exists(&id) {
-                        context.create_fn(id, func, pos)?;
-                    } else {
-                        match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
-                            Some(scope) => scope.create_fn(id, func, pos)?,
-                            None => context.create_fn(id, func, pos)?
-                        }
-                    }
-                    Ok((None, Return::None))
-                } else { panic!("type checking doesn't work") }
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let p: Params = context.arg(":p")?;
+    let body: Closure = context.arg(":body")?;
+    let return_type: Type = context.arg(":return_type")?;
+    let pos = context.arg_pos(":body");
+    let func = Function::new(p, Some(return_type), body.node, false).with_env(body.env).with_self(&id, &pos, context.frame_id());
+    let len = context.scopes.len();
+    if context.fn_exists(&id) {
+        context.create_fn(id, func, pos)?;
+    } else {
+        match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
+            Some(scope) => scope.create_fn(id, func, pos)?,
+            None => context.create_fn(id, func, pos)?
+        }
+    }
+    Ok((None, Return::None))
 }
 fn _def_inline(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let p = context.get_var(&":p".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":body".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if let Value::Params(p) = p {
-            if let Value::Closure(body) = body {
-                let func = Function::new(p, None, Box::new(body), true);
-                let len = context.scopes.len();
-                if context.fn_exists(&id) {
-                    context.create_fn(id, func, pos)?;
-                } else {
-                    match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
-                        Some(scope) => scope.create_fn(id, func, pos)?,
-                        None => context.create_fn(id, func, pos)?
-                    }
-                }
-                Ok((None, Return::None))
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let p: Params = context.arg(":p")?;
+    let body: Closure = context.arg(":body")?;
+    let pos = context.arg_pos(":body");
+    let func = Function::new(p, None, body.node, true).with_env(body.env);
+    let len = context.scopes.len();
+    if context.fn_exists(&id) {
+        context.create_fn(id, func, pos)?;
+    } else {
+        match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
+            Some(scope) => scope.create_fn(id, func, pos)?,
+            None => context.create_fn(id, func, pos)?
+        }
+    }
+    Ok((None, Return::None))
 }
 fn _def_return_inline(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let p = context.get_var(&":p".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    let return_type = context.get_var(&":return_type".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":body".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if let Value::Params(p) = p {
-            if let Value::Closure(body) = body {
-                if let Value::Type(return_type) = return_type {
-                    let func = Function::new(p, Some(return_type), Box::new(body), true);
-                    let len = context.scopes.len();
-                    if context.fn_exists(&id) {
-                        context.create_fn(id, func, pos)?;
-                    } else {
-                        match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
-                            Some(scope) => scope.create_fn(id, func, pos)?,
-                            None => context.create_fn(id, func, pos)?
-                        }
-                    }
-                    Ok((None, Return::None))
-                } else { panic!("type checking doesn't work") }
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let p: Params = context.arg(":p")?;
+    let body: Closure = context.arg(":body")?;
+    let return_type: Type = context.arg(":return_type")?;
+    let pos = context.arg_pos(":body");
+    let func = Function::new(p, Some(return_type), body.node, true).with_env(body.env);
+    let len = context.scopes.len();
+    if context.fn_exists(&id) {
+        context.create_fn(id, func, pos)?;
+    } else {
+        match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
+            Some(scope) => scope.create_fn(id, func, pos)?,
+            None => context.create_fn(id, func, pos)?
+        }
+    }
+    Ok((None, Return::None))
 }
 fn _def_global(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let p = context.get_var(&":p".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":body".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if let Value::Params(p) = p {
-            if let Value::Closure(body) = body {
-                let func = Function::new(p, None, Box::new(body), false);
-                context.create_fn_global(id, func, pos)?;
-                Ok((None, Return::None))
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let p: Params = context.arg(":p")?;
+    let body: Closure = context.arg(":body")?;
+    let pos = context.arg_pos(":body");
+    let func = Function::new(p, None, body.node, false).with_env(body.env);
+    context.create_fn_global(id, func, pos)?;
+    Ok((None, Return::None))
 }
 fn _def_return_global(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let p = context.get_var(&":p".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    let return_type = context.get_var(&":return_type".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":body".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if let Value::Params(p) = p {
-            if let Value::Closure(body) = body {
-                if let Value::Type(return_type) = return_type {
-                    let func = Function::new(p, Some(return_type), Box::new(body), false);
-                    context.create_fn_global(id, func, pos)?;
-                    Ok((None, Return::None))
-                } else { panic!("type checking doesn't work") }
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let p: Params = context.arg(":p")?;
+    let body: Closure = context.arg(":body")?;
+    let return_type: Type = context.arg(":return_type")?;
+    let pos = context.arg_pos(":body");
+    let func = Function::new(p, Some(return_type), body.node, false).with_env(body.env);
+    context.create_fn_global(id, func, pos)?;
+    Ok((None, Return::None))
 }
 fn _def_global_inline(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let p = context.get_var(&":p".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":body".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if let Value::Params(p) = p {
-            if let Value::Closure(body) = body {
-                let func = Function::new(p, None, Box::new(body), true);
-                context.create_fn_global(id, func, pos)?;
-                Ok((None, Return::None))
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let p: Params = context.arg(":p")?;
+    let body: Closure = context.arg(":body")?;
+    let pos = context.arg_pos(":body");
+    let func = Function::new(p, None, body.node, true).with_env(body.env);
+    context.create_fn_global(id, func, pos)?;
+    Ok((None, Return::None))
 }
 fn _def_return_global_inline(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let p = context.get_var(&":p".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    let return_type = context.get_var(&":return_type".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":body".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if let Value::Params(p) = p {
-            if let Value::Closure(body) = body {
-                if let Value::Type(return_type) = return_type {
-                    let func = Function::new(p, Some(return_type), Box::new(body), true);
-                    context.create_fn_global(id, func, pos)?;
-                    Ok((None, Return::None))
-                } else { panic!("type checking doesn't work") }
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let p: Params = context.arg(":p")?;
+    let body: Closure = context.arg(":body")?;
+    let return_type: Type = context.arg(":return_type")?;
+    let pos = context.arg_pos(":body");
+    let func = Function::new(p, Some(return_type), body.node, true).with_env(body.env);
+    context.create_fn_global(id, func, pos)?;
+    Ok((None, Return::None))
+}
+// struct
+// declares the type of the struct or variant and its constructor, which takes the fields in order, `(Point 1.0 2.0)`
+fn declare_struct(context: &mut Context, id: String, fields: Params, enum_name: Option<String>, pos: Position) -> Result<(), Error> {
+    let typ = match &enum_name {
+        Some(enum_name) => Type::Variant(enum_name.clone(), id.clone()),
+        None => Type::Struct(id.clone())
+    };
+    context.global.create_var(id.clone(), Value::Type(typ.clone()), false, pos.clone(), false)?;
+    let (name, fields) = (id.clone(), Rc::new(fields));
+    let constructor = NativFunction::new(fields.to_vec(), Some(typ), move |context| {
+        let mut values: Vec<Value> = vec![];
+        for (field, _, _) in fields.iter() { values.push(context.arg(field)?); }
+        Ok((Some(Value::Struct(Struct::new(name.clone(), enum_name.clone(), fields.clone(), values))), Return::None))
+    }, false);
+    context.create_native_fn(id, constructor, pos)
+}
+fn _struct(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let Key(id) = context.arg(":id")?;
+    let fields: Params = context.arg(":fields")?;
+    let pos = context.arg_pos(":id");
+    declare_struct(context, id, fields, None, pos)?;
+    Ok((None, Return::None))
+}
+// the variants are evaluated after the enum is declared so their fields can refer to it
+fn _enum(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let Key(id) = context.arg(":id")?;
+    let variants: Closure = context.arg(":variants")?;
+    let pos = context.arg_pos(":id");
+    let variants_pos = context.arg_pos(":variants");
+    let Node::Object { entries, pos:_ } = variants.node.as_ref() else {
+        let value = call_closure(&variants, context)?.0.unwrap_or(Value::None);
+        return Err(Error::ExpectedType(Type::Object, value.typ(), variants_pos))
+    };
+    context.global.create_var(id.clone(), Value::Type(Type::Enum(id.clone())), false, pos.clone(), false)?;
+    for (variant, node) in entries.iter() {
+        match interpret(node, context)?.0 {
+            Some(Value::Params(fields)) => declare_struct(context, variant.clone(), fields, Some(id.clone()), node.pos().clone())?,
+            value => return Err(Error::ExpectedType(Type::Params, value.unwrap_or(Value::None).typ(), node.pos().clone()))
+        }
+    }
+    Ok((None, Return::None))
 }
 // control flow
 fn _return(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap().clone();
+    let v: Value = context.arg("v")?;
     Ok((Some(v), Return::Return))
 }
-fn _break(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+fn _yield(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    // the yield has its own frame, the one below it has to be the generator's
+    let frame = context.frames.iter().rev().nth(1).map(|frame| frame.id);
+    if frame.is_none() || frame != context.generator { return Err(Error::YieldOutsideGenerator(context.arg_pos("v"))) }
+    Ok((Some(context.arg("v")?), Return::Yield))
+}
+fn _break(_context: &mut Context) -> Result<(Option<Value>, Return), Error> {
     Ok((None, Return::Break))
 }
+fn _continue(_context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    Ok((None, Return::Continue))
+}
+fn jump_label(context: &mut Context, ret: Return) -> Result<(Option<Value>, Return), Error> {
+    let Key(label) = context.arg(":label")?;
+    if !context.loops.contains(&Some(label.clone())) { return Err(Error::UnknownLabel(label, context.arg_pos(":label"))) }
+    context.label = Some(label);
+    Ok((None, ret))
+}
+fn _break_label(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    jump_label(context, Return::Break)
+}
+fn _continue_label(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    jump_label(context, Return::Continue)
+}
+// what a loop does with the result of its body, `Some` ends the loop with that result
+fn loop_exit(value: Option<Value>, ret: Return, context: &mut Context) -> Option<(Option<Value>, Return)> {
+    match ret {
+        Return::None => None,
+        Return::Break | Return::Continue => {
+            // a break or continue labeled for an outer loop ends this one too
+            if context.label.is_some() && context.loops.last() != Some(&context.label) { return Some((None, ret)) }
+            context.label = None;
+            if ret == Return::Break { Some((None, Return::None)) } else { None }
+        }
+        _ => Some((value, ret))
+    }
+}
+// registers a loop native together with its labeled overload `(... @label ...)`
+#[allow(clippy::type_complexity)]
+fn create_loop(context: &mut Context, id: &str, params: Params, body: fn(&mut Context) -> Result<(Option<Value>, Return), Error>, pos: &Position) -> Result<(), Error> {
+    for labeled in [false, true] {
+        let mut params = params.clone();
+        if labeled { params.insert(0, (":label".to_string(), Type::Key, false)); }
+        context.create_native_fn(id.to_string(), NativFunction {
+            params,
+            return_type: None,
+            body: Rc::new(move |context| {
+                let label = if labeled { Some(context.arg::<Key>(":label")?.0) } else { None };
+                context.loops.push(label);
+                let res = body(context);
+                context.loops.pop();
+                res
+            }),
+            inline: true
+        }, pos.clone())?;
+    }
+    Ok(())
+}
+// try
+fn error_object(e: &Error, trace: Vec<Position>) -> Result<Value, Error> {
+    let pos = e.pos().clone();
+    let mut positions: Vec<Value> = vec![];
+    for pos in trace.iter() {
+        let mut pos_scope = Scope::new();
+        pos_scope.create_var("path".to_string(), Value::String(pos.path.to_string()), true, pos.clone(), true)?;
+        pos_scope.create_var("ln".to_string(), Value::Int(pos.ln.start as i64 + 1), true, pos.clone(), true)?;
+        pos_scope.create_var("col".to_string(), Value::Int(pos.col.start as i64 + 1), true, pos.clone(), true)?;
+        positions.push(Value::Object(pos_scope));
+    }
+    let msg = e.to_string();
+    let mut obj_scope = Scope::new();
+    obj_scope.create_var("msg".to_string(), Value::String(msg.strip_prefix("ERROR: ").unwrap_or(&msg).to_string()), true, pos.clone(), true)?;
+    obj_scope.create_var("kind".to_string(), Value::String(e.kind().to_string()), true, pos.clone(), true)?;
+    obj_scope.create_var("code".to_string(), Value::Int(e.exit_code() as i64), true, pos.clone(), true)?;
+    obj_scope.create_var("trace".to_string(), Value::Vector(positions, Some(Type::Object)), true, pos, true)?;
+    Ok(Value::Object(obj_scope))
+}
+fn try_catch(context: &mut Context, catch: bool, finally: bool) -> Result<(Option<Value>, Return), Error> {
+    let body: Closure = context.arg(":body")?;
+    let handler: Closure = context.arg(":handler")?;
+    let id: Option<Key> = if catch { Some(context.arg(":id")?) } else { None };
+    let id_pos = context.arg_pos(":handler");
+    let finally: Option<Closure> = if finally { Some(context.arg(":finally")?) } else { None };
+    // the state an error leaves behind has to be rolled back before the handler can run
+    let (checkpoint, path) = (context.checkpoint(), context.path.clone());
+    // a resumed generator that yielded in the handler goes back into the handler, the error is still bound
+    let caught = context.resuming() && context.scopes.last().is_some_and(|scope| scope.vars.contains_key(":caught"));
+    let res = if caught { call_closure(&handler, context) } else {
+        match call_closure(&body, context) {
+            Err(e) if !matches!(e, Error::Exit(..)) => {
+                let mut positions = context.trace.split_off(checkpoint.2);
+                if positions.first() != Some(e.pos()) { positions.insert(0, e.pos().clone()) }
+                context.restore(checkpoint);
+                context.path = path;
+                if let Some(Key(id)) = id {
+                    context.create_var(id, error_object(&e, positions)?, false, id_pos.clone(), true)?;
+                }
+                let res = call_closure(&handler, context);
+                if let Ok((_, Return::Yield)) = &res {
+                    context.create_var(":caught".to_string(), Value::Bool(true), false, id_pos, true)?;
+                }
+                res
+            }
+            res => res
+        }
+    };
+    // a yield only suspends the body
+    if let Some(finally) = finally.filter(|_| !matches!(res, Ok((_, Return::Yield)))) {
+        context.scopes.truncate(checkpoint.0);
+        context.frames.truncate(checkpoint.1);
+        call_closure(&finally, context)?;
+    }
+    res
+}
+fn _try(context: &mut Context) -> Result<(Option<Value>, Return), Error> { try_catch(context, false, false) }
+fn _try_catch(context: &mut Context) -> Result<(Option<Value>, Return), Error> { try_catch(context, true, false) }
+fn _try_finally(context: &mut Context) -> Result<(Option<Value>, Return), Error> { try_catch(context, false, true) }
+fn _try_catch_finally(context: &mut Context) -> Result<(Option<Value>, Return), Error> { try_catch(context, true, true) }
 fn _do(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let node = context.get_var(&":node".to_string()).unwrap().clone();
-    if let Value::Closure(node) = node {
-        let res = interpret(&node, context)?;
-        Ok(res)
-    } else { panic!("type checking doesn't work") }
+    let node: Closure = context.arg(":node")?;
+    call_closure(&node, context)
 }
 fn _if(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let cond = context.get_var(&"cond".to_string()).unwrap();
-    let case = context.get_var(&"case".to_string()).unwrap();
-    let else_ = context.get_var(&"else".to_string()).unwrap();
-    if cond == &Value::Bool(true) {
-        Ok((Some(case.clone()), Return::None))
+    let cond: Value = context.arg("cond")?;
+    let case: Value = context.arg("case")?;
+    let else_: Value = context.arg("else")?;
+    if cond == Value::Bool(true) {
+        Ok((Some(case), Return::None))
     } else {
-        Ok((Some(else_.clone()), Return::None))
+        Ok((Some(else_), Return::None))
     }
 }
 fn _if_do(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let cond = context.get_var(&":cond".to_string()).unwrap();
-    let case = context.get_var(&":case".to_string()).unwrap().clone();
-    if let Value::Closure(case) = case {
-        if cond == &Value::Bool(true) {
-            return interpret(&case, context)
-        }
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
+    let cond: Value = context.arg(":cond")?;
+    let case: Closure = context.arg(":case")?;
+    if cond == Value::Bool(true) {
+        return call_closure(&case, context)
+    }
+    Ok((None, Return::None))
 }
 fn _if_do_else(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let cond = context.get_var(&":cond".to_string()).unwrap();
-    let case = context.get_var(&":case".to_string()).unwrap().clone();
-    let else_ = context.get_var(&":else".to_string()).unwrap().clone();
-    if let Value::Closure(case) = case {
-        if let Value::Closure(else_) = else_ {
-            if cond == &Value::Bool(true) {
-                interpret(&case, context)
-            } else {
-                interpret(&else_, context)
-            }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let cond: Value = context.arg(":cond")?;
+    let case: Closure = context.arg(":case")?;
+    let else_: Closure = context.arg(":else")?;
+    if cond == Value::Bool(true) {
+        call_closure(&case, context)
+    } else {
+        call_closure(&else_, context)
+    }
 }
 fn _for(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":id".to_string()).unwrap().clone();
-    let iter = context.get_var(&":iter".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    if let Value::Closure(body) = body {
-        if let Value::Key(id) = id {
-            if let Value::Vector(values, _) = iter {
-                for v in values {
-                    context.create_var(id.clone(), v, false, pos.clone(), true);
-                    let (value, ret) = interpret(&body, context)?;
-                    if ret == Return::Break { break }
-                    if ret == Return::Return { return Ok((value, ret)) }
-                }
-                Ok((None, Return::None))
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let mut iter: Iter = context.arg(":iter")?;
+    let iter_pos = context.arg_pos(":iter");
+    let body: Closure = context.arg(":body")?;
+    // a resumed generator continues with the value it yielded at
+    let mut next = if context.resuming() { context.get_var(&id).cloned() } else { iter.next(context, &iter_pos)? };
+    while let Some(v) = next {
+        context.create_var(id.clone(), v, false, pos.clone(), true)?;
+        let (value, ret) = call_closure(&body, context)?;
+        if ret == Return::Yield {
+            context.create_var(":iter".to_string(), Value::Iterator(iter), false, iter_pos, true)?;
+            return Ok((value, ret))
+        }
+        if let Some(res) = loop_exit(value, ret, context) { return Ok(res) }
+        next = iter.next(context, &iter_pos)?;
+    }
+    Ok((None, Return::None))
 }
 fn _for_length(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":id".to_string()).unwrap().clone();
-    let length = context.get_var(&":length".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    if let Value::Closure(body) = body {
-        if let Value::Key(id) = id {
-            if let Value::Int(length) = length {
-                for i in 0..length {
-                    context.create_var(id.clone(), Value::Int(i), false, pos.clone(), true);
-                    let (value, ret) = interpret(&body, context)?;
-                    if ret == Return::Break { break }
-                    if ret == Return::Return { return Ok((value, ret)) }
-                }
-                Ok((None, Return::None))
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let length: i64 = context.arg(":length")?;
+    let body: Closure = context.arg(":body")?;
+    let start: i64 = if context.resuming() { context.arg(&id)? } else { 0 };
+    for i in start..length {
+        context.create_var(id.clone(), Value::Int(i), false, pos.clone(), true)?;
+        let (value, ret) = call_closure(&body, context)?;
+        if let Some(res) = loop_exit(value, ret, context) { return Ok(res) }
+    }
+    Ok((None, Return::None))
 }
 fn _for_range(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let pos = context.get_var_pos(&":id".to_string()).unwrap().clone();
-    let start = context.get_var(&":start".to_string()).unwrap().clone();
-    let end = context.get_var(&":end".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    if let Value::Closure(body) = body {
-        if let Value::Key(id) = id {
-            if let Value::Int(start) = start {
-                if let Value::Int(end) = end {
-                    for i in start..end {
-                        context.create_var(id.clone(), Value::Int(i), false, pos.clone(), true);
-                        let (value, ret) = interpret(&body, context)?;
-                        if ret == Return::Break { break }
-                        if ret == Return::Return { return Ok((value, ret)) }
-                    }
-                    Ok((None, Return::None))
-                } else { panic!("type checking doesn't work") }
-            } else { panic!("type checking doesn't work") }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-fn _while(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let cond = context.get_var(&":cond".to_string()).unwrap().clone();
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    if let Value::Closure(body) = body {
-        if let Value::Closure(cond) = cond {
-            while let (Some(Value::Bool(true)), _) = interpret(&cond, context)? {
-                let (value, ret) = interpret(&body, context)?;
-                if ret == Return::Break { break }
-                if ret == Return::Return { return Ok((value, ret)) }
-            }
-            Ok((None, Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-fn _loop(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let body = context.get_var(&":body".to_string()).unwrap().clone();
-    if let Value::Closure(body) = body {
-        loop {
-            let (value, ret) = interpret(&body, context)?;
-            if ret == Return::Break { break }
-            if ret == Return::Return { return Ok((value, ret)) }
-        }
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-// +
-fn _add_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::Int)) = nums {
-        let mut sum: i64 = 0;
-        if let Value::Int(n) = n { sum = *n; }
-        for n in nums.iter() {
-            if let Value::Int(n) = n {
-                sum += *n;
-            }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let start: i64 = context.arg(":start")?;
+    let end: i64 = context.arg(":end")?;
+    let body: Closure = context.arg(":body")?;
+    let start: i64 = if context.resuming() { context.arg(&id)? } else { start };
+    for i in start..end {
+        context.create_var(id.clone(), Value::Int(i), false, pos.clone(), true)?;
+        let (value, ret) = call_closure(&body, context)?;
+        if let Some(res) = loop_exit(value, ret, context) { return Ok(res) }
+    }
+    Ok((None, Return::None))
+}
+// the names a pattern binds if it matches the value, a type matches its values and binds the fields of structs
+// a quoted pattern like `#[x _ rest*]`, `#${x int y _}` or `#(Binary op l r)` destructures the value,
+// words bind, `_` matches anything, types test the type and every other node is compared by value
+fn match_node(node: &Node, v: &Value, context: &mut Context, bindings: &mut Vec<(String, Value)>) -> Result<bool, Error> {
+    match node {
+        Node::Word { v: id, pos:_ } => {
+            if id != "_" { bindings.push((id.clone(), v.clone())); }
+            Ok(true)
         }
-        Ok((Some(Value::Int(sum)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _add_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::Float)) = nums {
-        let mut sum: f64 = 0.0;
-        if let Value::Float(n) = n { sum = *n; }
-        for n in nums.iter() {
-            if let Value::Float(n) = n {
-                sum += *n;
+        Node::Type { v: typ, pos:_ } => Ok(v.typ().matches(typ)),
+        Node::Vector { nodes, pos:_ } => {
+            let Value::Vector(values, typ) = v else { return Ok(false) };
+            let rest = match nodes.last() {
+                Some(Node::Word { v: id, pos:_ }) => id.strip_suffix('*'),
+                _ => None
+            };
+            let nodes = if rest.is_some() { &nodes[..nodes.len() - 1] } else { &nodes[..] };
+            if values.len() < nodes.len() || (rest.is_none() && values.len() != nodes.len()) { return Ok(false) }
+            for (node, v) in nodes.iter().zip(values) {
+                if !match_node(node, v, context, bindings)? { return Ok(false) }
             }
-        }
-        Ok((Some(Value::Float(sum)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _add_str(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::String)) = nums {
-        let mut string = String::new();
-        if let Value::String(n) = n { string.push_str(n.as_str()); }
-        for n in nums.iter() {
-            if let Value::String(n) = n {
-                string.push_str(n.as_str());
+            if let Some(id) = rest {
+                if id != "_" { bindings.push((id.to_string(), Value::Vector(values[nodes.len()..].to_vec(), typ.clone()))); }
             }
+            Ok(true)
         }
-        Ok((Some(Value::String(string)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-// -
-fn _sub_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::Int)) = nums {
-        let mut sum: i64 = 0;
-        if let Value::Int(n) = n { sum = *n; }
-        for n in nums.iter() {
-            if let Value::Int(n) = n {
-                sum -= *n;
+        Node::Object { entries, pos:_ } => {
+            for (key, node) in entries {
+                let field = match v {
+                    Value::Object(scope) => scope.get_var(key).cloned(),
+                    Value::Struct(v) => v.get(key).cloned(),
+                    _ => return Ok(false)
+                };
+                let Some(field) = field else { return Ok(false) };
+                // `${x int}` binds the field to its key if it has the type
+                if let Node::Type { v: typ, pos:_ } = node.as_ref() {
+                    if !field.typ().matches(typ) { return Ok(false) }
+                    bindings.push((key.clone(), field));
+                } else if !match_node(node, &field, context, bindings)? { return Ok(false) }
             }
+            Ok(true)
         }
-        Ok((Some(Value::Int(sum)), Return::None))
-    } else { panic!("type checking doesn't work"); }
-}
-fn _neg_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    if let Value::Int(n) = n {
-        Ok((Some(Value::Int(-n)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _sub_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::Float)) = nums {
-        let mut sum: f64 = 0.0;
-        if let Value::Float(n) = n { sum = *n; }
-        for n in nums.iter() {
-            if let Value::Float(n) = n {
-                sum -= *n;
-            }
-        }
-        Ok((Some(Value::Float(sum)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _neg_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    if let Value::Float(n) = n {
-        Ok((Some(Value::Float(-n)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-// *
-fn _mul_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::Int)) = nums {
-        let mut sum: i64 = 0;
-        if let Value::Int(n) = n { sum = *n; }
-        for n in nums.iter() {
-            if let Value::Int(n) = n {
-                sum *= *n;
+        Node::Node { head, args, pos:_ } => {
+            let typ = match interpret(head, context)?.0 {
+                Some(Value::Type(typ)) => typ,
+                value => return Err(Error::ExpectedType(Type::Type, value.unwrap_or(Value::None).typ(), head.pos().clone()))
+            };
+            if !v.typ().matches(&typ) { return Ok(false) }
+            match v {
+                Value::Struct(v) if v.values.len() == args.len() => {
+                    for (node, v) in args.iter().zip(v.values.iter()) {
+                        if !match_node(node, v, context, bindings)? { return Ok(false) }
+                    }
+                    Ok(true)
+                }
+                // `(int n)` binds the value itself if it has the type
+                _ if args.len() == 1 => match_node(&args[0], v, context, bindings),
+                _ => Ok(args.is_empty())
             }
         }
-        Ok((Some(Value::Int(sum)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _mul_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::Float)) = nums {
-        let mut sum: f64 = 0.0;
-        if let Value::Float(n) = n { sum = *n; }
-        for n in nums.iter() {
-            if let Value::Float(n) = n {
-                sum *= *n;
+        _ => Ok(interpret(node, context)?.0.as_ref() == Some(v))
+    }
+}
+fn match_pattern(pattern: &Value, v: &Value, context: &mut Context, bindings: &mut Vec<(String, Value)>) -> Result<bool, Error> {
+    match pattern {
+        Value::Type(typ) => if v.typ().matches(typ) {
+            if let Value::Struct(v) = v {
+                bindings.extend(v.fields.iter().map(|(field, _, _)| field.clone()).zip(v.values.iter().cloned()));
             }
+            Ok(true)
+        } else { Ok(false) }
+        Value::Closure(pattern) => match_node(&pattern.node, v, context, bindings),
+        _ => Ok(pattern == v)
+    }
+}
+// binds the names of a matching pattern in the current scope and checks the guard
+fn match_arm(pattern: &Value, guard: Option<&Closure>, v: &Value, context: &mut Context, pos: &Position) -> Result<bool, Error> {
+    let mut bindings = vec![];
+    if !match_pattern(pattern, v, context, &mut bindings)? { return Ok(false) }
+    for (id, value) in bindings { context.create_var(id, value, false, pos.clone(), true)?; }
+    match guard {
+        Some(guard) => match call_closure(guard, context)?.0 {
+            Some(Value::Bool(matched)) => Ok(matched),
+            value => Err(Error::ExpectedType(Type::Bool, value.unwrap_or(Value::None).typ(), guard.node.pos().clone()))
         }
-        Ok((Some(Value::Float(sum)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _mul_str(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let s = context.get_var(&"s".to_string()).unwrap();
-    let n = context.get_var(&"n".to_string()).unwrap();
-    if let Value::String(s) = s {
-        if let Value::Int(n) = n {
-            Ok((Some(Value::String(s.repeat(max::<i64>(*n, 0) as usize))), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-// /
-fn _div_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::Int)) = nums {
-        let mut sum: i64 = 0;
-        if let Value::Int(n) = n { sum = *n; }
-        for n in nums.iter() {
-            if let Value::Int(n) = n {
-                sum /= *n;
+        None => Ok(true)
+    }
+}
+fn _match(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let v: Value = context.arg(":v")?;
+    let arms: Vec<Value> = context.arg(":arms")?;
+    let pos = context.arg_pos(":v");
+    let arms_pos = context.arg_pos(":arms");
+    // a resumed generator goes back into the arm it yielded in with its bindings
+    let resumed: Option<(i64, Scope)> = if context.resuming() { Some((context.arg(":arm")?, context.arg(":bindings")?)) } else { None };
+    // arms are `pattern #body` or `pattern @if #guard #body`
+    let mut arms = arms.into_iter();
+    let mut arm: i64 = -1;
+    while let Some(pattern) = arms.next() {
+        arm += 1;
+        let mut next = arms.next();
+        let guard = if next == Some(Value::Key("if".to_string())) {
+            let guard = arms.next();
+            next = arms.next();
+            match guard {
+                Some(Value::Closure(guard)) => Some(guard),
+                guard => return Err(Error::ExpectedType(Type::Closure, guard.unwrap_or(Value::None).typ(), arms_pos))
             }
-        }
-        Ok((Some(Value::Int(sum)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _div_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::Float)) = nums {
-        let mut sum: f64 = 0.0;
-        if let Value::Float(n) = n { sum = *n; }
-        for n in nums.iter() {
-            if let Value::Float(n) = n {
-                sum /= *n;
+        } else { None };
+        let Some(Value::Closure(body)) = next else {
+            return Err(Error::ExpectedType(Type::Closure, next.unwrap_or(Value::None).typ(), arms_pos))
+        };
+        let matched = match &resumed {
+            Some((resumed_arm, _)) if *resumed_arm != arm => continue,
+            Some((_, scope)) => {
+                context.scopes.push(scope.clone());
+                Ok(true)
             }
-        }
-        Ok((Some(Value::Float(sum)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-// %
-fn _mod_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::Int)) = nums {
-        let mut sum: i64 = 0;
-        if let Value::Int(n) = n { sum = *n; }
-        for n in nums.iter() {
-            if let Value::Int(n) = n {
-                sum %= *n;
+            None => {
+                context.push(); // the arm's own scope
+                match_arm(&pattern, guard.as_ref(), &v, context, &arms_pos)
             }
-        }
-        Ok((Some(Value::Int(sum)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _mod_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let n = context.get_var(&"n".to_string()).unwrap();
-    let nums = context.get_var(&"nums".to_string()).unwrap();
-    if let Value::Vector(nums, Some(Type::Float)) = nums {
-        let mut sum: f64 = 0.0;
-        if let Value::Float(n) = n { sum = *n; }
-        for n in nums.iter() {
-            if let Value::Float(n) = n {
-                sum %= *n;
+        };
+        match matched {
+            Ok(true) => {
+                let res = call_closure(&body, context);
+                let scope = context.pop();
+                if let (Ok((_, Return::Yield)), Some(scope)) = (&res, scope) {
+                    context.create_var(":arm".to_string(), Value::Int(arm), false, arms_pos.clone(), true)?;
+                    context.create_var(":bindings".to_string(), Value::Object(scope), false, arms_pos, true)?;
+                }
+                return res
             }
+            Ok(false) => { context.pop(); }
+            Err(e) => { context.pop(); return Err(e) }
         }
-        Ok((Some(Value::Float(sum)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-// =
-fn _eq(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let a = context.get_var(&"a".to_string()).unwrap();
-    let b = context.get_var(&"b".to_string()).unwrap();
-    Ok((Some(Value::Bool(a == b)), Return::None))
-}
-// !=
-fn _neq(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let a = context.get_var(&"a".to_string()).unwrap();
-    let b = context.get_var(&"b".to_string()).unwrap();
-    Ok((Some(Value::Bool(a == b)), Return::None))
-}
-// >
-fn _gt_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let a = context.get_var(&"a".to_string()).unwrap();
-    let b = context.get_var(&"b".to_string()).unwrap();
-    if let Value::Int(a) = a {
-        if let Value::Int(b) = b {
-            Ok((Some(Value::Bool(a > b)), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-fn _gt_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let a = context.get_var(&"a".to_string()).unwrap();
-    let b = context.get_var(&"b".to_string()).unwrap();
-    if let Value::Float(a) = a {
-        if let Value::Float(b) = b {
-            Ok((Some(Value::Bool(a > b)), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-// >=
-fn _ge_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let a = context.get_var(&"a".to_string()).unwrap();
-    let b = context.get_var(&"b".to_string()).unwrap();
-    if let Value::Int(a) = a {
-        if let Value::Int(b) = b {
-            Ok((Some(Value::Bool(a >= b)), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-fn _ge_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let a = context.get_var(&"a".to_string()).unwrap();
-    let b = context.get_var(&"b".to_string()).unwrap();
-    if let Value::Float(a) = a {
-        if let Value::Float(b) = b {
-            Ok((Some(Value::Bool(a >= b)), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-// <
-fn _lt_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let a = context.get_var(&"a".to_string()).unwrap();
-    let b = context.get_var(&"b".to_string()).unwrap();
-    if let Value::Int(a) = a {
-        if let Value::Int(b) = b {
-            Ok((Some(Value::Bool(a < b)), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-fn _lt_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let a = context.get_var(&"a".to_string()).unwrap();
-    let b = context.get_var(&"b".to_string()).unwrap();
-    if let Value::Float(a) = a {
-        if let Value::Float(b) = b {
-            Ok((Some(Value::Bool(a < b)), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-// <=
-fn _le_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let a = context.get_var(&"a".to_string()).unwrap();
-    let b = context.get_var(&"b".to_string()).unwrap();
-    if let Value::Int(a) = a {
-        if let Value::Int(b) = b {
-            Ok((Some(Value::Bool(a <= b)), Return::None))
-        } else { panic!("type checkng doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-fn _le_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let a = context.get_var(&"a".to_string()).unwrap();
-    let b = context.get_var(&"b".to_string()).unwrap();
-    if let Value::Float(a) = a {
-        if let Value::Float(b) = b {
-            Ok((Some(Value::Bool(a <= b)), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-// len
-fn _len_vec(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Vector(v, t) = v {
-        Ok((Some(Value::Int(v.len() as i64)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _len_str(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::String(v) = v {
-        Ok((Some(Value::Int(v.len() as i64)), Return::None))
-    } else { panic!("type checking doesn't work") }
+    }
+    Err(Error::NoMatch(v, pos))
 }
-// int
-fn _int_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    Ok((Some(context.get_var(&"v".to_string()).unwrap().clone()), Return::None))
-}
-fn _int_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Float(v) = v {
-        Ok((Some(Value::Int(*v as i64)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _int_char(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Char(v) = v {
-        Ok((Some(Value::Int(*v as i64)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _int_bool(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Bool(v) = v {
-        Ok((Some(Value::Int(*v as i64)), Return::None))
-    } else { panic!("type checking doesn't work") }
+fn _while(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let cond: Closure = context.arg(":cond")?;
+    let body: Closure = context.arg(":body")?;
+    // a resumed generator is still in the body
+    while context.resuming() || matches!(call_closure(&cond, context)?, (Some(Value::Bool(true)), _)) {
+        let (value, ret) = call_closure(&body, context)?;
+        if let Some(res) = loop_exit(value, ret, context) { return Ok(res) }
+    }
+    Ok((None, Return::None))
 }
-fn _int_str(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::String(v) = v {
-        if let Ok(v) = v.parse::<i64>() {
-            Ok((Some(Value::Int(v)), Return::None))
-        } else {
-            Err(Error::ParseInt(v.clone()))
-        }
-    } else { panic!("type checking doesn't work") }
+fn _loop(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let body: Closure = context.arg(":body")?;
+    loop {
+        let (value, ret) = call_closure(&body, context)?;
+        if let Some(res) = loop_exit(value, ret, context) { return Ok(res) }
+    }
 }
-// float
-fn _float_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Int(v) = v {
-        Ok((Some(Value::Float(*v as f64)), Return::None))
-    } else { panic!("type checking doesn't work") }
+// iter
+fn _range_step(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let start: i64 = context.arg(":start")?;
+    let end: i64 = context.arg(":end")?;
+    let step: i64 = context.arg(":step")?;
+    if step == 0 { return Err(Error::Error("the step of a range can't be 0".to_string(), EXIT_RUNTIME, context.arg_pos(":step"))) }
+    Ok((Some(Value::Iterator(Iter::Range(start, end, step))), Return::None))
 }
-fn _float_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    Ok((Some(context.get_var(&"v".to_string()).unwrap().clone()), Return::None))
+fn _collect(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let iter: Iter = context.arg(":iter")?;
+    let pos = context.arg_pos(":iter");
+    Ok((Some(iter.collect(context, &pos)?), Return::None))
 }
+// int
+fn _int_str(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let v: String = context.arg("v")?;
+    match v.parse::<i64>() {
+        Ok(v) => Ok((Some(Value::Int(v)), Return::None)),
+        Err(_) => Err(Error::ParseInt(v, context.arg_pos("v")))
+    }
+}
+// float
 fn _float_str(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::String(v) = v {
-        if let Ok(v) = v.parse::<f64>() {
-            Ok((Some(Value::Float(v)), Return::None))
-        } else {
-            Err(Error::ParseFloat(v.clone()))
-        }
-    } else { panic!("type checking doesn't work") }
+    let v: String = context.arg("v")?;
+    match v.parse::<f64>() {
+        Ok(v) => Ok((Some(Value::Float(v)), Return::None)),
+        Err(_) => Err(Error::ParseFloat(v, context.arg_pos("v")))
+    }
 }
 // bool
-fn _bool_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Int(v) = v {
-        Ok((Some(Value::Bool(*v != 0)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _bool_float(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Float(v) = v {
-        Ok((Some(Value::Bool(*v != 0.0)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _bool_char(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Char(v) = v {
-        Ok((Some(Value::Bool(*v as u8 != 0)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _bool_bool(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    Ok((Some(context.get_var(&"v".to_string()).unwrap().clone()), Return::None))
-}
 fn _bool_str(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::String(v) = v {
-        if let Ok(v) = v.parse::<bool>() {
-            Ok((Some(Value::Bool(v)), Return::None))
-        } else {
-            Err(Error::ParseBool(v.clone()))
-        }
-    } else { panic!("type checking doesn't work") }
+    let v: String = context.arg("v")?;
+    match v.parse::<bool>() {
+        Ok(v) => Ok((Some(Value::Bool(v)), Return::None)),
+        Err(_) => Err(Error::ParseBool(v, context.arg_pos("v")))
+    }
 }
 // char
-fn _char_int(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Int(v) = v {
-        Ok((Some(Value::Char(*v as u8 as char)), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _char_char(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    Ok((Some(context.get_var(&"v".to_string()).unwrap().clone()), Return::None))
-}
 fn _char_str(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::String(v) = v {
-        if let Ok(v) = v.parse::<char>() {
-            Ok((Some(Value::Char(v)), Return::None))
-        } else {
-            Err(Error::ParseChar(v.clone()))
-        }
-    } else { panic!("type checking doesn't work") }
-}
-// str
-fn _str(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    Ok((Some(Value::String(context.get_var(&"v".to_string()).unwrap().to_string())), Return::None))
-}
-// key
-fn _key(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    Ok((Some(Value::Key(context.get_var(&"v".to_string()).unwrap().to_string())), Return::None))
-}
-// path
-fn _path_key(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let head = context.get_var(&"head".to_string()).unwrap().clone();
-    let sub = context.get_var(&"sub".to_string()).unwrap().clone();
-    if let Value::Key(head) = head {
-        if let Value::Key(sub) = sub {
-            Ok((Some(Value::Path(Path::new(PathWays::Key(head), sub))), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-fn _path_path(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let head = context.get_var(&"head".to_string()).unwrap().clone();
-    let sub = context.get_var(&"sub".to_string()).unwrap().clone();
-    if let Value::Path(head) = head {
-        if let Value::Key(sub) = sub {
-            Ok((Some(Value::Path(Path::new(PathWays::Path(Box::new(head)), sub))), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-fn _path_index(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let head = context.get_var(&"head".to_string()).unwrap().clone();
-    let sub = context.get_var(&"sub".to_string()).unwrap().clone();
-    if let Value::Index(head) = head {
-        if let Value::Key(sub) = sub {
-            Ok((Some(Value::Path(Path::new(PathWays::Index(Box::new(head)), sub))), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let v: String = context.arg("v")?;
+    match v.parse::<char>() {
+        Ok(v) => Ok((Some(Value::Char(v)), Return::None)),
+        Err(_) => Err(Error::ParseChar(v, context.arg_pos("v")))
+    }
 }
 // index
+fn index(context: &mut Context, head: PathWays) -> Result<(Option<Value>, Return), Error> {
+    let idx: i64 = context.arg("idx")?;
+    if idx < 0 { return Err(Error::IllegalNegativeIndex(idx, context.arg_pos("idx"))) }
+    Ok((Some(Value::Index(Index::new(head, idx as usize))), Return::None))
+}
 fn _index_key(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let head = context.get_var(&"head".to_string()).unwrap().clone();
-    let idx = context.get_var(&"idx".to_string()).unwrap();
-    if let Value::Key(head) = head {
-        if let Value::Int(idx) = idx {
-            if *idx < 0 { return Err(Error::IllegalNegativeIndex(*idx)) }
-            Ok((Some(Value::Index(Index::new(PathWays::Key(head), *idx as usize))), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(head) = context.arg("head")?;
+    index(context, PathWays::Key(head))
 }
 fn _index_path(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let head = context.get_var(&"head".to_string()).unwrap().clone();
-    let idx = context.get_var(&"idx".to_string()).unwrap();
-    if let Value::Path(head) = head {
-        if let Value::Int(idx) = idx {
-            if *idx < 0 { return Err(Error::IllegalNegativeIndex(*idx)) }
-            Ok((Some(Value::Index(Index::new(PathWays::Path(Box::new(head)), *idx as usize))), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let head: Path = context.arg("head")?;
+    index(context, PathWays::Path(Box::new(head)))
 }
 fn _index_index(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let head = context.get_var(&"head".to_string()).unwrap().clone();
-    let idx = context.get_var(&"idx".to_string()).unwrap();
-    if let Value::Index(head) = head {
-        if let Value::Int(idx) = idx {
-            if *idx < 0 { return Err(Error::IllegalNegativeIndex(*idx)) }
-            Ok((Some(Value::Index(Index::new(PathWays::Index(Box::new(head)), *idx as usize))), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let head: Index = context.arg("head")?;
+    index(context, PathWays::Index(Box::new(head)))
 }
 // vec
-fn _vec(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let t = context.get_var(&"t".to_string()).unwrap();
-    if let Value::Type(t) = t {
-        Ok((Some(Value::Type(Type::Vector(Some(Box::new(t.clone()))))), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
 fn _vec_of(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap().clone();
-    let t = context.get_var(&"t".to_string()).unwrap().clone();
-    if let Value::Type(t) = t {
-        if let Value::Vector(values, typ) = v {
-            if let Some(typ) = typ { if typ != t { return Err(Error::ExpectedType(t, typ)) } }
-            Ok((Some(Value::Vector(values, Some(t))), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let t: Type = context.arg("t")?;
+    let v: Value = context.arg("v")?;
+    let pos = context.arg_pos("v");
+    if let Value::Vector(values, typ) = v {
+        if let Some(typ) = typ { if typ != t { return Err(Error::ExpectedType(t, typ, pos)) } }
+        Ok((Some(Value::Vector(values, Some(t))), Return::None))
+    } else {
+        Err(Error::ExpectedType(Type::Vector(None), v.typ(), pos))
+    }
 }
 fn _vec_push(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let value = context.get_var(&":v".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id)) }
-        match context.get_var_mut(&id) {
-            Some(values) => if let Value::Vector(values, typ) = values {
-                match typ {
-                    Some(typ) => if value.typ() == *typ {
-                        values.push(value);
-                        Ok((None, Return::None))
-                    } else {
-                        return Err(Error::ExpectedType(typ.clone(), value.typ()))
-                    }
-                    None => {
-                        *typ = Some(value.typ());
-                        values.push(value);
-                        Ok((None, Return::None))
-                    }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let value: Value = context.arg(":v")?;
+    let v_pos = context.arg_pos(":v");
+    if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id, pos)) }
+    match context.get_var_mut(&id) {
+        Some(values) => if let Value::Vector(values, typ) = values {
+            match typ {
+                Some(typ) => if value.typ().matches(typ) {
+                    values.push(value);
+                    Ok((None, Return::None))
+                } else {
+                    Err(Error::ExpectedType(typ.clone(), value.typ(), v_pos))
+                }
+                None => {
+                    *typ = Some(value.typ());
+                    values.push(value);
+                    Ok((None, Return::None))
                 }
-            } else {
-                Err(Error::ExpectedType(Type::Vector(Some(Box::new(Type::Any))), values.typ()))
             }
-            None => Err(Error::NotDefined(id))
+        } else {
+            Err(Error::ExpectedType(Type::Vector(Some(Box::new(Type::Any))), values.typ(), pos))
         }
-    } else { panic!("type checking doesn't work") }
+        None => Err(Error::NotDefined(id, pos))
+    }
 }
 fn _str_push(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let c = context.get_var(&":c".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if let Value::Char(c) = c {
-            if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id)) }
-            match context.get_var_mut(&id) {
-                Some(s) => if let Value::String(s) = s {
-                    s.push(c);
-                    Ok((None, Return::None))
-                } else {
-                    Err(Error::ExpectedType(Type::String, s.typ()))
-                }
-                None => Err(Error::NotDefined(id))
-            }
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let c: char = context.arg(":c")?;
+    if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id, pos)) }
+    match context.get_var_mut(&id) {
+        Some(s) => if let Value::String(s) = s {
+            s.push(c);
+            Ok((None, Return::None))
+        } else {
+            Err(Error::ExpectedType(Type::String, s.typ(), pos))
+        }
+        None => Err(Error::NotDefined(id, pos))
+    }
 }
 fn _vec_pop(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id)) }
-        match context.get_var_mut(&id) {
-            Some(values) => if let Value::Vector(values, _) = values {
-                Ok((values.pop(), Return::None))
-            } else {
-                Err(Error::ExpectedType(Type::Vector(Some(Box::new(Type::Any))), values.typ()))
-            }
-            None => Err(Error::NotDefined(id))
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id, pos)) }
+    match context.get_var_mut(&id) {
+        Some(values) => if let Value::Vector(values, _) = values {
+            Ok((values.pop(), Return::None))
+        } else {
+            Err(Error::ExpectedType(Type::Vector(Some(Box::new(Type::Any))), values.typ(), pos))
         }
-    } else { panic!("type checking doesn't work") }
+        None => Err(Error::NotDefined(id, pos))
+    }
 }
 fn _vec_pop_idx(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let idx = context.get_var(&":idx".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id)) }
-        match context.get_var_mut(&id) {
-            Some(values) => if let Value::Vector(values, _) = values {
-                if let Value::Int(idx) = idx {
-                    let idx = if idx < 0 { values.len() - idx.abs() as usize } else { idx as usize };
-                    match values.get(idx) {
-                        Some(_) => Ok((Some(values.remove(idx)), Return::None)),
-                        None => Err(Error::IndexOutOfRange(idx, values.len()))
-                    }
-                } else { panic!("type checking doesn't work") }
-            } else {
-                Err(Error::ExpectedType(Type::Vector(Some(Box::new(Type::Any))), values.typ()))
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let idx: i64 = context.arg(":idx")?;
+    if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id, pos)) }
+    match context.get_var_mut(&id) {
+        Some(values) => if let Value::Vector(values, _) = values {
+            let idx = if idx < 0 { values.len() - idx.unsigned_abs() as usize } else { idx as usize };
+            match values.get(idx) {
+                Some(_) => Ok((Some(values.remove(idx)), Return::None)),
+                None => Err(Error::IndexOutOfRange(idx, values.len(), pos))
             }
-            None => Err(Error::NotDefined(id))
+        } else {
+            Err(Error::ExpectedType(Type::Vector(Some(Box::new(Type::Any))), values.typ(), pos))
         }
-    } else { panic!("type checking doesn't work") }
+        None => Err(Error::NotDefined(id, pos))
+    }
 }
 fn _str_pop(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id)) }
-        match context.get_var_mut(&id) {
-            Some(s) => if let Value::String(s) = s {
-                Ok((match s.pop() { Some(c) => Some(Value::Char(c)), _ => None }, Return::None))
-            } else {
-                Err(Error::ExpectedType(Type::String, s.typ()))
-            }
-            None => Err(Error::NotDefined(id))
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id, pos)) }
+    match context.get_var_mut(&id) {
+        Some(s) => if let Value::String(s) = s {
+            Ok((s.pop().map(Value::Char), Return::None))
+        } else {
+            Err(Error::ExpectedType(Type::String, s.typ(), pos))
         }
-    } else { panic!("type checking doesn't work") }
+        None => Err(Error::NotDefined(id, pos))
+    }
 }
 fn _str_pop_idx(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let id = context.get_var(&":id".to_string()).unwrap().clone();
-    let idx = context.get_var(&":idx".to_string()).unwrap().clone();
-    if let Value::Key(id) = id {
-        if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id)) }
-        match context.get_var_mut(&id) {
-            Some(s) => if let Value::String(s) = s {
-                if let Value::Int(idx) = idx {
-                    let idx = if idx < 0 { s.len() - idx.abs() as usize } else { idx as usize };
-                    match s.get(idx .. idx + 1) {
-                        Some(_) => Ok((Some(Value::Char(s.remove(idx))), Return::None)),
-                        None => Err(Error::IndexOutOfRange(idx, s.len()))
-                    }
-                } else { panic!("type checking doesn't work") }
-            } else {
-                Err(Error::ExpectedType(Type::Vector(Some(Box::new(Type::Any))), s.typ()))
-            }
-            None => Err(Error::NotDefined(id))
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let idx: i64 = context.arg(":idx")?;
+    if context.is_mutable(&id) == Some(false) { return Err(Error::Immutable(id, pos)) }
+    match context.get_var_mut(&id) {
+        Some(s) => if let Value::String(s) = s {
+            let len = s.chars().count();
+            let idx = find_index(&idx, len);
+            if idx < len { Ok((Some(Value::Char(s.remove(char_offset(s, idx)))), Return::None)) }
+            else { Err(Error::IndexOutOfRange(idx, len, pos)) }
+        } else {
+            Err(Error::ExpectedType(Type::Vector(Some(Box::new(Type::Any))), s.typ(), pos))
         }
-    } else { panic!("type checking doesn't work") }
-}
-fn _contains(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let values = context.get_var(&"values".to_string()).unwrap();
-    let value = context.get_var(&"value".to_string()).unwrap();
-    if let Value::Vector(values, _) = values {
-        Ok((Some(Value::Bool(values.contains(value))), Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _contains_str(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let s = context.get_var(&"s".to_string()).unwrap();
-    let c = context.get_var(&"c".to_string()).unwrap();
-    if let Value::String(s) = s {
-        if let Value::Char(c) = c {
-            Ok((Some(Value::Bool(s.contains(*c))), Return::None))
-        } else { panic!("type checking doesn't work") }
-    } else { panic!("type checking doesn't work") }
-}
-// type
-fn _type(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    Ok((Some(Value::Type(context.get_var(&"v".to_string()).unwrap().typ())), Return::None))
+        None => Err(Error::NotDefined(id, pos))
+    }
 }
 // io
-fn _write(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Vector(values, Some(Type::Any)) = v {
-        for v in values.iter() { print!("{v}"); }
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
-}
-fn _print(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let v = context.get_var(&"v".to_string()).unwrap();
-    if let Value::Vector(values, Some(Type::Any)) = v {
-        for v in values.iter() { println!("{v}"); }
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
-}
 fn _input(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let msg = context.get_var(&"msg".to_string()).unwrap();
-    if let Value::String(msg) = msg {
-        print!("{msg}");
-        std::io::stdout().flush();
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input);
-        input = input.trim().to_string();
-        Ok((Some(Value::String(input)), Return::None))
-    } else { panic!("type checking doesn't work") }
+    let msg: String = context.arg("msg")?;
+    print!("{msg}");
+    let _ = std::io::stdout().flush();
+    let mut input = String::new();
+    let _ = std::io::stdin().read_line(&mut input); // a closed stdin reads as an empty line
+    Ok((Some(Value::String(input.trim().to_string())), Return::None))
 }
 fn _error(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let msg = context.get_var(&"msg".to_string()).unwrap();
-    if let Value::String(msg) = msg {
-        Err(Error::Error(msg.clone()))
-    } else { panic!("type checking doesn't work") }
+    let msg: String = context.arg("msg")?;
+    Err(Error::Error(msg, EXIT_RUNTIME, context.arg_pos("msg")))
+}
+fn _error_code(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let msg: String = context.arg("msg")?;
+    let code: i64 = context.arg("code")?;
+    // 0 would look like success and the os only keeps the lowest byte
+    if !(1..=255).contains(&code) {
+        return Err(Error::Error(format!("the exit code {code} of an error has to be in 1..=255"), EXIT_RUNTIME, context.arg_pos("code")))
+    }
+    Err(Error::Error(msg, code as i32, context.arg_pos("msg")))
+}
+// str
+// fills a string up to a width of chars
+fn pad(s: String, width: i64, fill: char, left: bool) -> String {
+    let fill: String = std::iter::repeat_n(fill, (width.max(0) as usize).saturating_sub(s.chars().count())).collect();
+    if left { fill + &s } else { s + &fill }
 }
 // fs
+// an import is looked up next to the importing file and then in the directories of `NODY_PATH`
+pub fn resolve_import(path: &String, pos: &Position) -> Result<(String, String), Error> {
+    let importer_dir = std::path::Path::new(pos.path.as_ref()).parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+    let nody_path = env::var_os("NODY_PATH").map(|dirs| env::split_paths(&dirs).collect()).unwrap_or(vec![]);
+    for dir in std::iter::once(importer_dir).chain(nody_path) {
+        let file = dir.join(path);
+        if !file.is_file() { continue }
+        if let Ok(canonical) = fs::canonicalize(&file) { return Ok((canonical.display().to_string(), file.display().to_string())) }
+    }
+    Err(Error::ImportNotFound(path.clone(), pos.clone()))
+}
+// the chain of imports that leads back to the file, the importing file counts as its start
+fn import_cycle(canonical: &String, file: &String, pos: &Position, context: &Context) -> Result<(), Error> {
+    let mut chain = context.importing.clone();
+    if chain.is_empty() {
+        if let Ok(root) = fs::canonicalize(pos.path.as_ref()) { chain.push((root.display().to_string(), pos.path.to_string())) }
+    }
+    match chain.iter().position(|(importing, _)| importing == canonical) {
+        Some(start) => Err(Error::ImportCycle(chain[start..].iter().map(|(_, file)| file.clone()).chain(std::iter::once(file.clone())).collect(), pos.clone())),
+        None => Ok(())
+    }
+}
 fn _import(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let path = context.get_var(&"path".to_string()).unwrap();
-    if let Value::String(path) = path {
-        let mut file_context = Context::new(path.clone(), context.std_path.clone());
-        file_context.global = context.global.clone();
-        run_file_context(path, &mut file_context)?;
-        context.global = file_context.global;
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
+    let path: String = context.arg("path")?;
+    let pos = context.arg_pos("path");
+    let (canonical, file) = resolve_import(&path, &pos)?;
+    if context.imported.contains(&canonical) { return Ok((None, Return::None)) }
+    import_cycle(&canonical, &file, &pos, context)?;
+    context.importing.push((canonical.clone(), file.clone()));
+    let prev_path = std::mem::replace(&mut context.path, file.clone());
+    context.call(false);
+    let res = run_file_context(&file, context);
+    context.after_call(false);
+    context.path = prev_path;
+    context.importing.pop();
+    res?;
+    context.imported.insert(canonical);
+    Ok((None, Return::None))
+}
+// `(import "geom.nd" @geom)` binds what the module defines as an object
+fn _import_module(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let path: String = context.arg("path")?;
+    let Key(id) = context.arg(":id")?;
+    let pos = context.arg_pos(":id");
+    let module = import_module(&path, &context.arg_pos("path"), context)?;
+    let len = context.scopes.len();
+    match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
+        Some(scope) => scope.create_var(id, Value::Object(module), false, pos, false)?,
+        None => context.create_var(id, Value::Object(module), false, pos, false)?
+    }
+    Ok((None, Return::None))
+}
+// every module is loaded once, later imports of the same file get the same object
+pub fn import_module(path: &String, pos: &Position, context: &mut Context) -> Result<Scope, Error> {
+    let (canonical, file) = resolve_import(path, pos)?;
+    if let Some(module) = context.modules.get(&canonical) { return Ok(module.clone()) }
+    import_cycle(&canonical, &file, pos, context)?;
+    context.importing.push((canonical.clone(), file.clone()));
+    let res = run_module(&file, pos, context);
+    context.importing.pop();
+    let module = res?;
+    context.modules.insert(canonical, module.clone());
+    Ok(module)
+}
+// runs a file on a global scope of its own, the module is what it defines on top of the std
+fn run_module(path: &String, pos: &Position, context: &mut Context) -> Result<Scope, Error> {
+    let Ok(text) = fs::read_to_string(path) else { return Err(Error::ImportNotFound(path.clone(), pos.clone())) };
+    let node = scan_context(path, text, context)?;
+    let prev_path = std::mem::replace(&mut context.path, path.clone());
+    let global = std::mem::replace(&mut context.global, context.prelude.clone());
+    let imported = std::mem::take(&mut context.imported);
+    let exports = std::mem::take(&mut context.exports);
+    context.call(false);
+    let frame = context.frame_id();
+    // the top level runs in the scope of the module frame so its definitions are still there at the end
+    let nodes = match &node {
+        Node::Body { nodes, pos:_ } => nodes.as_slice(),
+        node => std::slice::from_ref(node)
+    };
+    let mut res = Ok(());
+    for node in nodes.iter() {
+        match run_node(node, context) {
+            Ok((_, Return::None)) => {}
+            Ok(_) => break,
+            Err(e) => {
+                res = Err(e);
+                break
+            }
+        }
+    }
+    let locals = context.frame_locals().first().cloned().unwrap_or_else(Scope::new);
+    context.after_call(false);
+    let module_global = std::mem::replace(&mut context.global, global);
+    context.imported = imported;
+    let exports = std::mem::replace(&mut context.exports, exports);
+    context.path = prev_path;
+    res?;
+    // the global definitions of the module are the ones after the std's
+    let mut scope = locals;
+    for (id, var) in module_global.vars.into_iter() {
+        if !context.prelude.vars.contains_key(&id) { scope.vars.entry(id).or_insert(var); }
+    }
+    for (id, defs) in module_global.funcs.into_iter() {
+        let skip = context.prelude.funcs.get(&id).map(Vec::len).unwrap_or(0);
+        scope.funcs.entry(id).or_default().extend(defs.into_iter().skip(skip));
+    }
+    for (id, defs) in module_global.native_funcs.into_iter() {
+        let skip = context.prelude.native_funcs.get(&id).map(Vec::len).unwrap_or(0);
+        scope.native_funcs.entry(id).or_default().extend(defs.into_iter().skip(skip));
+    }
+    scope.funcs.retain(|_, defs| !defs.is_empty());
+    scope.native_funcs.retain(|_, defs| !defs.is_empty());
+    // the functions of the module share one environment with all of its definitions, exported or not
+    let env = Env::new(Scope::new(), frame);
+    let with_module_env = |func: &mut Function| if func.env.as_ref().is_none_or(|env| env.frame == frame) {
+        func.env = Some(env.clone());
+    };
+    for (func, _) in scope.funcs.values_mut().flatten() { with_module_env(func); }
+    for (value, _, _) in scope.vars.values_mut() {
+        if let Value::Function(func) = value { with_module_env(func); }
+    }
+    *env.scope.borrow_mut() = scope.clone();
+    // without exports everything is exported
+    let mut module = if exports.is_empty() { scope } else {
+        let mut module = Scope::new();
+        for (id, pos) in exports {
+            let var = scope.vars.get(&id).cloned();
+            let funcs = scope.funcs.get(&id).cloned();
+            let native_funcs = scope.native_funcs.get(&id).cloned();
+            if var.is_none() && funcs.is_none() && native_funcs.is_none() { return Err(Error::NotDefined(id, pos)) }
+            if let Some(var) = var { module.vars.insert(id.clone(), var); }
+            if let Some(funcs) = funcs { module.funcs.insert(id.clone(), funcs); }
+            if let Some(native_funcs) = native_funcs { module.native_funcs.insert(id, native_funcs); }
+        }
+        module
+    };
+    // a function that isn't overloaded is also a value of the object, for `(geom @area)` and `geom.area` as an argument
+    for (id, defs) in module.funcs.iter() {
+        if let [(func, pos)] = defs.as_slice() {
+            if !module.vars.contains_key(id) { module.vars.insert(id.clone(), (Value::Function(func.clone()), false, pos.clone())); }
+        }
+    }
+    Ok(module)
 }
+fn _export(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let ids: Rest<Key> = context.arg(":ids")?;
+    let pos = context.arg_pos(":ids");
+    context.exports.extend(ids.0.into_iter().map(|Key(id)| (id, pos.clone())));
+    Ok((None, Return::None))
+}
+// `(import-var "geom.nd" @area @Point)` binds definitions of a module by their own names
 fn _import_var(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let path = context.get_var(&"path".to_string()).unwrap();
-    if let Value::String(path) = path {
-        let mut file_context = Context::new(path.clone(), context.std_path.clone());
-        file_context.global = context.global.clone();
-        run_file_context(path, &mut file_context)?;
-        context.global = file_context.global;
-        Ok((None, Return::None))
-    } else { panic!("type checking doesn't work") }
+    let path: String = context.arg("path")?;
+    let Rest(ids): Rest<Key> = context.arg(":ids")?;
+    let pos = context.arg_pos(":ids");
+    let module = import_module(&path, &context.arg_pos("path"), context)?;
+    let len = context.scopes.len();
+    let scope = match context.scopes.get_mut(len - 2) { // try to mutate the scope before the last
+        Some(scope) => scope,
+        None => &mut context.global
+    };
+    for Key(id) in ids {
+        let funcs = module.funcs.get(&id);
+        let native_funcs = module.native_funcs.get(&id);
+        for (func, _) in funcs.into_iter().flatten() { scope.create_fn(id.clone(), func.clone(), pos.clone())?; }
+        for (func, _) in native_funcs.into_iter().flatten() { scope.create_native_fn(id.clone(), func.clone(), pos.clone())?; }
+        match module.vars.get(&id) {
+            Some((Value::Function(_), _, _)) if funcs.is_some() => {}
+            Some((value, _, _)) => scope.create_var(id, value.clone(), false, pos.clone(), false)?,
+            None => if funcs.is_none() && native_funcs.is_none() { return Err(Error::NotDefined(id, pos)) }
+        }
+    }
+    Ok((None, Return::None))
 }
 fn _read_file(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
-    let path = context.get_var(&"path".to_string()).unwrap();
-    let pos = context.get_var_pos(&"path".to_string()).unwrap();
-    if let Value::String(path) = path {
-        match fs::read_to_string(path) {
-            Ok(content) => Ok((Some(Value::String(content)), Return::None)),
-            Err(_) => Err(Error::FileNotFound(path.clone()))
-        }
-    } else { panic!("type checking doesn't work") }
+    let path: String = context.arg("path")?;
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok((Some(Value::String(content)), Return::None)),
+        Err(_) => Err(Error::FileNotFound(path, context.arg_pos("path")))
+    }
 }
+// env
+// the os rejects these keys and values, `env::set_var` would panic on them
+fn _env_set(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let key: String = context.arg("key")?;
+    let value: String = context.arg("value")?;
+    if key.is_empty() || key.contains(['=', '\0']) {
+        return Err(Error::Error(format!("invalid environment variable name {key:?}"), EXIT_RUNTIME, context.arg_pos("key")))
+    }
+    if value.contains('\0') {
+        return Err(Error::Error(format!("invalid environment variable value {value:?}"), EXIT_RUNTIME, context.arg_pos("value")))
+    }
+    env::set_var(key, value);
+    Ok((None, Return::None))
+}
+fn _env_vars(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let pos = Position::new(0..0, 0..0, &context.path);
+    let mut obj_scope = Scope::new();
+    for (key, value) in env::vars_os() { // entries that aren't utf-8 are converted lossily
+        obj_scope.create_var(key.to_string_lossy().into_owned(), Value::String(value.to_string_lossy().into_owned()), true, pos.clone(), true)?;
+    }
+    Ok((Some(Value::Object(obj_scope)), Return::None))
+}
+fn _exit(context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let code: i64 = context.arg("code")?;
+    if !(0..=255).contains(&code) {
+        return Err(Error::Error(format!("the exit code {code} has to be in 0..=255"), EXIT_RUNTIME, context.arg_pos("code")))
+    }
+    Err(Error::Exit(code as i32, context.arg_pos("code")))
+}
+
+pub const STD_FILE: &str = "std.nd";
+pub const STD_EMBEDDED: &str = include_str!("../nody_std/std.nd");
 
 pub fn std_context(path: String, std_dir_path: Option<String>) -> Result<Context, Error> {
     let mut context = Context::new(path, std_dir_path.clone());
@@ -1144,85 +1091,97 @@ pub fn std_context(path: String, std_dir_path: Option<String>) -> Result<Context
     context.create_native_fn(String::from("let"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false), (":v".to_string(), Type::Any, false)],
         return_type: None,
-        body: _let,
+        body: Rc::new(_let),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("mut"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false), (":v".to_string(), Type::Any, false)],
         return_type: None,
-        body: _mut,
+        body: Rc::new(_mut),
+        inline: true
+    }, pos.clone())?;
+    context.create_native_fn(String::from("let-global"), NativFunction {
+        params: vec![(":id".to_string(), Type::Key, false), (":v".to_string(), Type::Any, false)],
+        return_type: None,
+        body: Rc::new(_let_global),
+        inline: true
+    }, pos.clone())?;
+    context.create_native_fn(String::from("mut-global"), NativFunction {
+        params: vec![(":id".to_string(), Type::Key, false), (":v".to_string(), Type::Any, false)],
+        return_type: None,
+        body: Rc::new(_mut_global),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("set"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false), (":v".to_string(), Type::Any, false)],
         return_type: None,
-        body: _set,
+        body: Rc::new(_set),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("set"), NativFunction {
         params: vec![(":path".to_string(), Type::Path, false), (":v".to_string(), Type::Any, false)],
         return_type: None,
-        body: _set_path,
+        body: Rc::new(_set_path),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("set"), NativFunction {
         params: vec![(":index".to_string(), Type::Index, false), (":v".to_string(), Type::Any, false)],
         return_type: None,
-        body: _set_index,
+        body: Rc::new(_set_index),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("get"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false)],
         return_type: Some(Type::Any),
-        body: _get,
+        body: Rc::new(_get),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("get"), NativFunction {
         params: vec![(":path".to_string(), Type::Path, false)],
         return_type: Some(Type::Any),
-        body: _get_path,
+        body: Rc::new(_get_path),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("get"), NativFunction {
         params: vec![(":index".to_string(), Type::Index, false)],
         return_type: Some(Type::Any),
-        body: _get_index,
+        body: Rc::new(_get_index),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("exist?"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false)],
         return_type: Some(Type::Bool),
-        body: _exist,
+        body: Rc::new(_exist),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("exist?"), NativFunction {
         params: vec![(":path".to_string(), Type::Path, false)],
         return_type: Some(Type::Bool),
-        body: _exist_path,
+        body: Rc::new(_exist_path),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("exist?"), NativFunction {
         params: vec![(":index".to_string(), Type::Index, false)],
         return_type: Some(Type::Bool),
-        body: _exist_index,
+        body: Rc::new(_exist_index),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("mut?"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false)],
         return_type: Some(Type::Bool),
-        body: _is_mut,
+        body: Rc::new(_is_mut),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("mut?"), NativFunction {
         params: vec![(":path".to_string(), Type::Path, false)],
         return_type: Some(Type::Bool),
-        body: _is_mut_path,
+        body: Rc::new(_is_mut_path),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("mut?"), NativFunction {
         params: vec![(":index".to_string(), Type::Index, false)],
         return_type: Some(Type::Bool),
-        body: _is_mut_index,
+        body: Rc::new(_is_mut_index),
         inline: true
     }, pos.clone())?;
     // def
@@ -1234,7 +1193,7 @@ pub fn std_context(path: String, std_dir_path: Option<String>) -> Result<Context
             (":return_type".to_string(), Type::Type, false),
         ],
         return_type: None,
-        body: _def_return,
+        body: Rc::new(_def_return),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("def"), NativFunction {
@@ -1244,7 +1203,7 @@ pub fn std_context(path: String, std_dir_path: Option<String>) -> Result<Context
             (":body".to_string(), Type::Closure, false),
         ],
         return_type: None,
-        body: _def,
+        body: Rc::new(_def),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("def-inline"), NativFunction {
@@ -1255,7 +1214,7 @@ pub fn std_context(path: String, std_dir_path: Option<String>) -> Result<Context
             (":return_type".to_string(), Type::Type, false),
         ],
         return_type: None,
-        body: _def_return_inline,
+        body: Rc::new(_def_return_inline),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("def-inline"), NativFunction {
@@ -1265,7 +1224,7 @@ pub fn std_context(path: String, std_dir_path: Option<String>) -> Result<Context
             (":body".to_string(), Type::Closure, false),
         ],
         return_type: None,
-        body: _def_inline,
+        body: Rc::new(_def_inline),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("def-global"), NativFunction {
@@ -1276,7 +1235,7 @@ pub fn std_context(path: String, std_dir_path: Option<String>) -> Result<Context
             (":return_type".to_string(), Type::Type, false),
         ],
         return_type: None,
-        body: _def_return_global,
+        body: Rc::new(_def_return_global),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("def-global"), NativFunction {
@@ -1286,7 +1245,7 @@ pub fn std_context(path: String, std_dir_path: Option<String>) -> Result<Context
             (":body".to_string(), Type::Closure, false),
         ],
         return_type: None,
-        body: _def_global,
+        body: Rc::new(_def_global),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("def-global-inline"), NativFunction {
@@ -1297,7 +1256,7 @@ pub fn std_context(path: String, std_dir_path: Option<String>) -> Result<Context
             (":return_type".to_string(), Type::Type, false),
         ],
         return_type: None,
-        body: _def_return_global_inline,
+        body: Rc::new(_def_return_global_inline),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("def-global-inline"), NativFunction {
@@ -1307,545 +1266,465 @@ pub fn std_context(path: String, std_dir_path: Option<String>) -> Result<Context
             (":body".to_string(), Type::Closure, false),
         ],
         return_type: None,
-        body: _def_global_inline,
+        body: Rc::new(_def_global_inline),
+        inline: true
+    }, pos.clone())?;
+    // struct
+    context.create_native_fn(String::from("struct"), NativFunction {
+        params: vec![(":id".to_string(), Type::Key, false), (":fields".to_string(), Type::Params, false)],
+        return_type: None,
+        body: Rc::new(_struct),
+        inline: true
+    }, pos.clone())?;
+    context.create_native_fn(String::from("enum"), NativFunction {
+        params: vec![(":id".to_string(), Type::Key, false), (":variants".to_string(), Type::Closure, false)],
+        return_type: None,
+        body: Rc::new(_enum),
         inline: true
     }, pos.clone())?;
+    // fn
+    context.create_native_fn(String::from("fn"), native_fn(|p: Params, body: Closure|
+        Function::new(p, None, body.node, false).with_env(body.env)), pos.clone())?;
+    context.create_native_fn(String::from("fn"), native_fn(|p: Params, body: Closure, return_type: Type|
+        Function::new(p, Some(return_type), body.node, false).with_env(body.env)), pos.clone())?;
     // control flow
     context.create_native_fn(String::from("return"), NativFunction {
         params: vec![("v".to_string(), Type::Any, false)],
         return_type: Some(Type::Any),
-        body: _return,
+        body: Rc::new(_return),
+        inline: false
+    }, pos.clone())?;
+    context.create_native_fn(String::from("yield"), NativFunction {
+        params: vec![("v".to_string(), Type::Any, false)],
+        return_type: Some(Type::Any),
+        body: Rc::new(_yield),
+        inline: false
+    }, pos.clone())?;
+    context.create_native_fn(String::from("break"), NativFunction {
+        params: vec![],
+        return_type: None,
+        body: Rc::new(_break),
         inline: false
     }, pos.clone())?;
     context.create_native_fn(String::from("break"), NativFunction {
+        params: vec![(":label".to_string(), Type::Key, false)],
+        return_type: None,
+        body: Rc::new(_break_label),
+        inline: false
+    }, pos.clone())?;
+    context.create_native_fn(String::from("continue"), NativFunction {
         params: vec![],
         return_type: None,
-        body: _break,
+        body: Rc::new(_continue),
+        inline: false
+    }, pos.clone())?;
+    context.create_native_fn(String::from("continue"), NativFunction {
+        params: vec![(":label".to_string(), Type::Key, false)],
+        return_type: None,
+        body: Rc::new(_continue_label),
         inline: false
     }, pos.clone())?;
     context.create_native_fn(String::from("do"), NativFunction {
         params: vec![(":node".to_string(), Type::Closure, false)],
         return_type: Some(Type::Any),
-        body: _do,
+        body: Rc::new(_do),
         inline: true
     }, pos.clone())?;
-    context.create_native_fn(String::from("?"), NativFunction {
+    context.create_native_fn(String::from("try"), NativFunction {
         params: vec![
-            ("cond".to_string(), Type::Bool, false),
-            ("case".to_string(), Type::Any, false),
-            ("else".to_string(), Type::Any, false)
+            (":body".to_string(), Type::Closure, false),
+            (":handler".to_string(), Type::Closure, false)
         ],
         return_type: Some(Type::Any),
-        body: _if,
-        inline: false
+        body: Rc::new(_try),
+        inline: true
     }, pos.clone())?;
-    context.create_native_fn(String::from("if"), NativFunction {
+    context.create_native_fn(String::from("try"), NativFunction {
         params: vec![
-            (":cond".to_string(), Type::Bool, false),
-            (":case".to_string(), Type::Any, false)
+            (":body".to_string(), Type::Closure, false),
+            (":id".to_string(), Type::Key, false),
+            (":handler".to_string(), Type::Closure, false)
         ],
         return_type: Some(Type::Any),
-        body: _if_do,
+        body: Rc::new(_try_catch),
         inline: true
     }, pos.clone())?;
-    context.create_native_fn(String::from("if"), NativFunction {
+    context.create_native_fn(String::from("try"), NativFunction {
         params: vec![
-            (":cond".to_string(), Type::Bool, false),
-            (":case".to_string(), Type::Any, false),
-            (":else".to_string(), Type::Any, false)
+            (":body".to_string(), Type::Closure, false),
+            (":handler".to_string(), Type::Closure, false),
+            (":finally".to_string(), Type::Closure, false)
         ],
         return_type: Some(Type::Any),
-        body: _if_do_else,
+        body: Rc::new(_try_finally),
         inline: true
     }, pos.clone())?;
-    context.create_native_fn(String::from("for"), NativFunction {
+    context.create_native_fn(String::from("try"), NativFunction {
         params: vec![
+            (":body".to_string(), Type::Closure, false),
             (":id".to_string(), Type::Key, false),
-            (":iter".to_string(), Type::Vector(Some(Box::new(Type::Any))), false),
-            (":body".to_string(), Type::Closure, false)
+            (":handler".to_string(), Type::Closure, false),
+            (":finally".to_string(), Type::Closure, false)
         ],
-        return_type: None,
-        body: _for,
+        return_type: Some(Type::Any),
+        body: Rc::new(_try_catch_finally),
         inline: true
     }, pos.clone())?;
-    context.create_native_fn(String::from("for"), NativFunction {
+    context.create_native_fn(String::from("?"), NativFunction {
         params: vec![
-            (":id".to_string(), Type::Key, false),
-            (":length".to_string(), Type::Int, false),
-            (":body".to_string(), Type::Closure, false)
+            ("cond".to_string(), Type::Bool, false),
+            ("case".to_string(), Type::Any, false),
+            ("else".to_string(), Type::Any, false)
         ],
-        return_type: None,
-        body: _for_length,
-        inline: true
+        return_type: Some(Type::Any),
+        body: Rc::new(_if),
+        inline: false
     }, pos.clone())?;
-    context.create_native_fn(String::from("for"), NativFunction {
+    context.create_native_fn(String::from("if"), NativFunction {
         params: vec![
-            (":id".to_string(), Type::Key, false),
-            (":start".to_string(), Type::Int, false),
-            (":end".to_string(), Type::Int, false),
-            (":body".to_string(), Type::Closure, false)
+            (":cond".to_string(), Type::Bool, false),
+            (":case".to_string(), Type::Any, false)
         ],
-        return_type: None,
-        body: _for_range,
+        return_type: Some(Type::Any),
+        body: Rc::new(_if_do),
         inline: true
     }, pos.clone())?;
-    context.create_native_fn(String::from("while"), NativFunction {
+    context.create_native_fn(String::from("if"), NativFunction {
         params: vec![
-            (":cond".to_string(), Type::Closure, false),
-            (":body".to_string(), Type::Closure, false)
+            (":cond".to_string(), Type::Bool, false),
+            (":case".to_string(), Type::Any, false),
+            (":else".to_string(), Type::Any, false)
         ],
-        return_type: None,
-        body: _while,
+        return_type: Some(Type::Any),
+        body: Rc::new(_if_do_else),
         inline: true
     }, pos.clone())?;
-    context.create_native_fn(String::from("loop"), NativFunction {
-        params: vec![(":body".to_string(), Type::Closure, false)],
-        return_type: None,
-        body: _loop,
+    context.create_native_fn(String::from("match"), NativFunction {
+        params: vec![(":v".to_string(), Type::Any, false), (":arms".to_string(), Type::Any, true)],
+        return_type: Some(Type::Any),
+        body: Rc::new(_match),
         inline: true
     }, pos.clone())?;
+    create_loop(&mut context, "for", vec![
+        (":id".to_string(), Type::Key, false),
+        (":iter".to_string(), iterable(), false),
+        (":body".to_string(), Type::Closure, false)
+    ], _for, &pos)?;
+    create_loop(&mut context, "for", vec![
+        (":id".to_string(), Type::Key, false),
+        (":length".to_string(), Type::Int, false),
+        (":body".to_string(), Type::Closure, false)
+    ], _for_length, &pos)?;
+    create_loop(&mut context, "for", vec![
+        (":id".to_string(), Type::Key, false),
+        (":start".to_string(), Type::Int, false),
+        (":end".to_string(), Type::Int, false),
+        (":body".to_string(), Type::Closure, false)
+    ], _for_range, &pos)?;
+    create_loop(&mut context, "while", vec![
+        (":cond".to_string(), Type::Closure, false),
+        (":body".to_string(), Type::Closure, false)
+    ], _while, &pos)?;
+    create_loop(&mut context, "loop", vec![(":body".to_string(), Type::Closure, false)], _loop, &pos)?;
     // +
-    context.create_native_fn(String::from("+"), NativFunction {
-        params: vec![("n".to_string(), Type::Int, false), ("nums".to_string(), Type::Int, true)],
-        return_type: Some(Type::Int),
-        body: _add_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("+"), NativFunction {
-        params: vec![("n".to_string(), Type::Float, false), ("nums".to_string(), Type::Float, true)],
-        return_type: Some(Type::Float),
-        body: _add_float,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("+"), NativFunction {
-        params: vec![("n".to_string(), Type::String, false), ("nums".to_string(), Type::String, true)],
-        return_type: Some(Type::String),
-        body: _add_str,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("+"), native_fn(|n: i64, nums: Rest<i64>| nums.0.iter().fold(n, |sum, n| sum + n)), pos.clone())?;
+    context.create_native_fn(String::from("+"), native_fn(|n: f64, nums: Rest<f64>| nums.0.iter().fold(n, |sum, n| sum + n)), pos.clone())?;
+    context.create_native_fn(String::from("+"), native_fn(|n: String, nums: Rest<String>| nums.0.iter().fold(n, |sum, n| sum + n)), pos.clone())?;
     // -
-    context.create_native_fn(String::from("-"), NativFunction {
-        params: vec![("n".to_string(), Type::Int, false)],
-        return_type: Some(Type::Int),
-        body: _neg_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("-"), NativFunction {
-        params: vec![("n".to_string(), Type::Float, false)],
-        return_type: Some(Type::Float),
-        body: _neg_float,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("-"), NativFunction {
-        params: vec![("n".to_string(), Type::Int, false), ("nums".to_string(), Type::Int, true)],
-        return_type: Some(Type::Int),
-        body: _sub_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("-"), NativFunction {
-        params: vec![("n".to_string(), Type::Float, false), ("nums".to_string(), Type::Float, true)],
-        return_type: Some(Type::Float),
-        body: _sub_float,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("-"), native_fn(|n: i64| -n), pos.clone())?;
+    context.create_native_fn(String::from("-"), native_fn(|n: f64| -n), pos.clone())?;
+    context.create_native_fn(String::from("-"), native_fn(|n: i64, nums: Rest<i64>| nums.0.iter().fold(n, |sum, n| sum - n)), pos.clone())?;
+    context.create_native_fn(String::from("-"), native_fn(|n: f64, nums: Rest<f64>| nums.0.iter().fold(n, |sum, n| sum - n)), pos.clone())?;
     // *
-    context.create_native_fn(String::from("*"), NativFunction {
-        params: vec![("n".to_string(), Type::Int, false), ("nums".to_string(), Type::Int, true)],
-        return_type: Some(Type::Int),
-        body: _mul_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("*"), NativFunction {
-        params: vec![("n".to_string(), Type::Float, false), ("nums".to_string(), Type::Float, true)],
-        return_type: Some(Type::Float),
-        body: _mul_float,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("*"), NativFunction {
-        params: vec![("s".to_string(), Type::String, false), ("n".to_string(), Type::Int, false)],
-        return_type: Some(Type::String),
-        body: _mul_str,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("*"), native_fn(|n: i64, nums: Rest<i64>| nums.0.iter().fold(n, |sum, n| sum * n)), pos.clone())?;
+    context.create_native_fn(String::from("*"), native_fn(|n: f64, nums: Rest<f64>| nums.0.iter().fold(n, |sum, n| sum * n)), pos.clone())?;
+    context.create_native_fn(String::from("*"), native_fn(|s: String, n: i64| s.repeat(max(n, 0) as usize)), pos.clone())?;
     // /
-    context.create_native_fn(String::from("/"), NativFunction {
-        params: vec![("n".to_string(), Type::Int, false), ("nums".to_string(), Type::Int, true)],
-        return_type: Some(Type::Int),
-        body: _div_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("/"), NativFunction {
-        params: vec![("n".to_string(), Type::Float, false), ("nums".to_string(), Type::Float, true)],
-        return_type: Some(Type::Float),
-        body: _div_float,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("/"), native_fn(|n: i64, nums: Rest<i64>| nums.0.iter().fold(n, |sum, n| sum / n)), pos.clone())?;
+    context.create_native_fn(String::from("/"), native_fn(|n: f64, nums: Rest<f64>| nums.0.iter().fold(n, |sum, n| sum / n)), pos.clone())?;
     // %
-    context.create_native_fn(String::from("%"), NativFunction {
-        params: vec![("n".to_string(), Type::Int, false), ("nums".to_string(), Type::Int, true)],
-        return_type: Some(Type::Int),
-        body: _mod_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("%"), NativFunction {
-        params: vec![("n".to_string(), Type::Float, false), ("nums".to_string(), Type::Float, true)],
-        return_type: Some(Type::Float),
-        body: _mod_float,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("%"), native_fn(|n: i64, nums: Rest<i64>| nums.0.iter().fold(n, |sum, n| sum % n)), pos.clone())?;
+    context.create_native_fn(String::from("%"), native_fn(|n: f64, nums: Rest<f64>| nums.0.iter().fold(n, |sum, n| sum % n)), pos.clone())?;
     // =
-    context.create_native_fn(String::from("="), NativFunction {
-        params: vec![("a".to_string(), Type::Any, false), ("b".to_string(), Type::Any, false)],
-        return_type: Some(Type::Bool),
-        body: _eq,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("="), native_fn(|a: Value, b: Value| a == b), pos.clone())?;
     // !=
-    context.create_native_fn(String::from("!="), NativFunction {
-        params: vec![("a".to_string(), Type::Any, false), ("b".to_string(), Type::Any, false)],
-        return_type: Some(Type::Bool),
-        body: _neq,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("!="), native_fn(|a: Value, b: Value| a == b), pos.clone())?;
     // >
-    context.create_native_fn(String::from(">"), NativFunction {
-        params: vec![("a".to_string(), Type::Int, false), ("b".to_string(), Type::Int, false)],
-        return_type: Some(Type::Bool),
-        body: _gt_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from(">"), NativFunction {
-        params: vec![("a".to_string(), Type::Float, false), ("b".to_string(), Type::Float, false)],
-        return_type: Some(Type::Bool),
-        body: _gt_float,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from(">"), native_fn(|a: i64, b: i64| a > b), pos.clone())?;
+    context.create_native_fn(String::from(">"), native_fn(|a: f64, b: f64| a > b), pos.clone())?;
     // >=
-    context.create_native_fn(String::from(">="), NativFunction {
-        params: vec![("a".to_string(), Type::Int, false), ("b".to_string(), Type::Int, false)],
-        return_type: Some(Type::Bool),
-        body: _ge_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from(">="), NativFunction {
-        params: vec![("a".to_string(), Type::Float, false), ("b".to_string(), Type::Float, false)],
-        return_type: Some(Type::Bool),
-        body: _ge_float,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from(">="), native_fn(|a: i64, b: i64| a >= b), pos.clone())?;
+    context.create_native_fn(String::from(">="), native_fn(|a: f64, b: f64| a >= b), pos.clone())?;
     // <
-    context.create_native_fn(String::from("<"), NativFunction {
-        params: vec![("a".to_string(), Type::Int, false), ("b".to_string(), Type::Int, false)],
-        return_type: Some(Type::Bool),
-        body: _lt_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("<"), NativFunction {
-        params: vec![("a".to_string(), Type::Float, false), ("b".to_string(), Type::Float, false)],
-        return_type: Some(Type::Bool),
-        body: _lt_float,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("<"), native_fn(|a: i64, b: i64| a < b), pos.clone())?;
+    context.create_native_fn(String::from("<"), native_fn(|a: f64, b: f64| a < b), pos.clone())?;
     // <=
-    context.create_native_fn(String::from("<="), NativFunction {
-        params: vec![("a".to_string(), Type::Int, false), ("b".to_string(), Type::Int, false)],
-        return_type: Some(Type::Bool),
-        body: _le_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("<="), NativFunction {
-        params: vec![("a".to_string(), Type::Float, false), ("b".to_string(), Type::Float, false)],
-        return_type: Some(Type::Bool),
-        body: _le_float,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("<="), native_fn(|a: i64, b: i64| a <= b), pos.clone())?;
+    context.create_native_fn(String::from("<="), native_fn(|a: f64, b: f64| a <= b), pos.clone())?;
     // len
-    context.create_native_fn(String::from("len"), NativFunction {
-        params: vec![("v".to_string(), Type::Vector(Some(Box::new(Type::Any))), false)],
-        return_type: Some(Type::Int),
-        body: _len_vec,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("len"), NativFunction {
-        params: vec![("v".to_string(), Type::String, false)],
-        return_type: Some(Type::Int),
-        body: _len_str,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("len"), native_fn(|v: Vec<Value>| v.len() as i64), pos.clone())?;
+    context.create_native_fn(String::from("len"), native_fn(|v: String| v.chars().count() as i64), pos.clone())?;
     // int
-    context.create_native_fn(String::from("int"), NativFunction {
-        params: vec![("v".to_string(), Type::Int, false)],
-        return_type: Some(Type::Int),
-        body: _int_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("int"), NativFunction {
-        params: vec![("v".to_string(), Type::Float, false)],
-        return_type: Some(Type::Int),
-        body: _int_float,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("int"), NativFunction {
-        params: vec![("v".to_string(), Type::Char, false)],
-        return_type: Some(Type::Int),
-        body: _int_char,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("int"), NativFunction {
-        params: vec![("v".to_string(), Type::Bool, false)],
-        return_type: Some(Type::Int),
-        body: _int_bool,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("int"), native_fn(|v: i64| v), pos.clone())?;
+    context.create_native_fn(String::from("int"), native_fn(|v: f64| v as i64), pos.clone())?;
+    context.create_native_fn(String::from("int"), native_fn(|v: char| v as i64), pos.clone())?;
+    context.create_native_fn(String::from("int"), native_fn(|v: bool| v as i64), pos.clone())?;
     context.create_native_fn(String::from("int"), NativFunction {
         params: vec![("v".to_string(), Type::String, false)],
         return_type: Some(Type::Int),
-        body: _int_str,
+        body: Rc::new(_int_str),
         inline: false
     }, pos.clone())?;
     // float
-    context.create_native_fn(String::from("float"), NativFunction {
-        params: vec![("v".to_string(), Type::Int, false)],
-        return_type: Some(Type::Float),
-        body: _float_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("float"), NativFunction {
-        params: vec![("v".to_string(), Type::Float, false)],
-        return_type: Some(Type::Float),
-        body: _float_float,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("float"), native_fn(|v: i64| v as f64), pos.clone())?;
+    context.create_native_fn(String::from("float"), native_fn(|v: f64| v), pos.clone())?;
     context.create_native_fn(String::from("float"), NativFunction {
         params: vec![("v".to_string(), Type::String, false)],
         return_type: Some(Type::Float),
-        body: _float_str,
+        body: Rc::new(_float_str),
         inline: false
     }, pos.clone())?;
     // bool
-    context.create_native_fn(String::from("bool"), NativFunction {
-        params: vec![("v".to_string(), Type::Int, false)],
-        return_type: Some(Type::Bool),
-        body: _bool_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("bool"), NativFunction {
-        params: vec![("v".to_string(), Type::Float, false)],
-        return_type: Some(Type::Bool),
-        body: _bool_float,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("bool"), NativFunction {
-        params: vec![("v".to_string(), Type::Char, false)],
-        return_type: Some(Type::Bool),
-        body: _bool_char,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("bool"), NativFunction {
-        params: vec![("v".to_string(), Type::Bool, false)],
-        return_type: Some(Type::Bool),
-        body: _bool_bool,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("bool"), native_fn(|v: i64| v != 0), pos.clone())?;
+    context.create_native_fn(String::from("bool"), native_fn(|v: f64| v != 0.0), pos.clone())?;
+    context.create_native_fn(String::from("bool"), native_fn(|v: char| v as u8 != 0), pos.clone())?;
+    context.create_native_fn(String::from("bool"), native_fn(|v: bool| v), pos.clone())?;
     context.create_native_fn(String::from("bool"), NativFunction {
         params: vec![("v".to_string(), Type::String, false)],
         return_type: Some(Type::Bool),
-        body: _bool_str,
+        body: Rc::new(_bool_str),
         inline: false
     }, pos.clone())?;
     // char
-    context.create_native_fn(String::from("char"), NativFunction {
-        params: vec![("v".to_string(), Type::Int, false)],
-        return_type: Some(Type::Char),
-        body: _char_int,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("char"), NativFunction {
-        params: vec![("v".to_string(), Type::Char, false)],
-        return_type: Some(Type::Char),
-        body: _char_char,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("char"), native_fn(|v: i64| v as u8 as char), pos.clone())?;
+    context.create_native_fn(String::from("char"), native_fn(|v: char| v), pos.clone())?;
     context.create_native_fn(String::from("char"), NativFunction {
         params: vec![("v".to_string(), Type::String, false)],
         return_type: Some(Type::Char),
-        body: _char_str,
+        body: Rc::new(_char_str),
         inline: false
     }, pos.clone())?;
     // str
-    context.create_native_fn(String::from("str"), NativFunction {
-        params: vec![("v".to_string(), Type::Any, false)],
-        return_type: Some(Type::String),
-        body: _str,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("str"), native_fn(|v: Value| v.to_string()), pos.clone())?;
     // key
-    context.create_native_fn(String::from("key"), NativFunction {
-        params: vec![("v".to_string(), Type::Any, false)],
-        return_type: Some(Type::Key),
-        body: _key,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("key"), native_fn(|v: Value| Key(v.to_string())), pos.clone())?;
     // path
-    context.create_native_fn(String::from("path"), NativFunction {
-        params: vec![("head".to_string(), Type::Key, false), ("sub".to_string(), Type::Key, false)],
-        return_type: Some(Type::Path),
-        body: _path_key,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("path"), NativFunction {
-        params: vec![("head".to_string(), Type::Path, false), ("sub".to_string(), Type::Key, false)],
-        return_type: Some(Type::Path),
-        body: _path_path,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("path"), NativFunction {
-        params: vec![("head".to_string(), Type::Index, false), ("sub".to_string(), Type::Key, false)],
-        return_type: Some(Type::Path),
-        body: _path_index,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("path"), native_fn(|head: Key, sub: Key| Path::new(PathWays::Key(head.0), sub.0)), pos.clone())?;
+    context.create_native_fn(String::from("path"), native_fn(|head: Path, sub: Key| Path::new(PathWays::Path(Box::new(head)), sub.0)), pos.clone())?;
+    context.create_native_fn(String::from("path"), native_fn(|head: Index, sub: Key| Path::new(PathWays::Index(Box::new(head)), sub.0)), pos.clone())?;
     // index
     context.create_native_fn(String::from("index"), NativFunction {
         params: vec![("head".to_string(), Type::Key, false), ("idx".to_string(), Type::Int, false)],
         return_type: Some(Type::Index),
-        body: _index_key,
+        body: Rc::new(_index_key),
         inline: false
     }, pos.clone())?;
     context.create_native_fn(String::from("index"), NativFunction {
         params: vec![("head".to_string(), Type::Path, false), ("idx".to_string(), Type::Int, false)],
         return_type: Some(Type::Index),
-        body: _index_path,
+        body: Rc::new(_index_path),
         inline: false
     }, pos.clone())?;
     context.create_native_fn(String::from("index"), NativFunction {
         params: vec![("head".to_string(), Type::Index, false), ("idx".to_string(), Type::Int, false)],
         return_type: Some(Type::Index),
-        body: _index_index,
+        body: Rc::new(_index_index),
         inline: false
     }, pos.clone())?;
     // vec
-    context.create_native_fn(String::from("vec"), NativFunction {
-        params: vec![("t".to_string(), Type::Type, false)],
-        return_type: Some(Type::Vector(Some(Box::new(Type::Any)))),
-        body: _vec,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("vec"), native_fn(|t: Type| Type::Vector(Some(Box::new(t)))), pos.clone())?;
     context.create_native_fn(String::from("vec"), NativFunction {
         params: vec![
             ("t".to_string(), Type::Type, false),
             ("v".to_string(), Type::Vector(Some(Box::new(Type::Any))), false)
         ],
         return_type: Some(Type::Vector(Some(Box::new(Type::Any)))),
-        body: _vec_of,
+        body: Rc::new(_vec_of),
         inline: false
     }, pos.clone())?;
     context.create_native_fn(String::from("push"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false), (":v".to_string(), Type::Any, false)],
         return_type: Some(Type::Any),
-        body: _vec_push,
+        body: Rc::new(_vec_push),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("str-push"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false), (":c".to_string(), Type::Char, false)],
         return_type: None,
-        body: _str_push,
+        body: Rc::new(_str_push),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("pop"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false)],
         return_type: Some(Type::Any),
-        body: _vec_pop,
+        body: Rc::new(_vec_pop),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("pop"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false), (":idx".to_string(), Type::Int, false)],
         return_type: Some(Type::Any),
-        body: _vec_pop_idx,
+        body: Rc::new(_vec_pop_idx),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("str-pop"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false)],
         return_type: Some(Type::Char),
-        body: _str_pop,
+        body: Rc::new(_str_pop),
         inline: true
     }, pos.clone())?;
     context.create_native_fn(String::from("str-pop"), NativFunction {
         params: vec![(":id".to_string(), Type::Key, false), (":idx".to_string(), Type::Int, false)],
         return_type: Some(Type::Char),
-        body: _str_pop_idx,
+        body: Rc::new(_str_pop_idx),
         inline: true
     }, pos.clone())?;
-    context.create_native_fn(String::from("contains"), NativFunction {
-        params: vec![
-            ("values".to_string(), Type::Vector(Some(Box::new(Type::Any))), false),
-            ("value".to_string(), Type::Any, false)
-        ],
-        return_type: Some(Type::Bool),
-        body: _contains,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("contains"), NativFunction {
-        params: vec![
-            ("s".to_string(), Type::String, false),
-            ("c".to_string(), Type::Char, false)
-        ],
-        return_type: Some(Type::Bool),
-        body: _contains_str,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("contains"), native_fn(|values: Vec<Value>, value: Value| values.contains(&value)), pos.clone())?;
+    context.create_native_fn(String::from("contains"), native_fn(|s: String, c: char| s.contains(c)), pos.clone())?;
+    // str, every index and width counts chars
+    context.create_native_fn(String::from("split"), native_fn(|s: String| s.split_whitespace().map(String::from).collect::<Vec<String>>()), pos.clone())?;
+    context.create_native_fn(String::from("split"), native_fn(|s: String, sep: String| if sep.is_empty() {
+        s.chars().map(String::from).collect::<Vec<String>>()
+    } else {
+        s.split(&sep).map(String::from).collect()
+    }), pos.clone())?;
+    context.create_native_fn(String::from("split"), native_fn(|s: String, sep: char| s.split(sep).map(String::from).collect::<Vec<String>>()), pos.clone())?;
+    context.create_native_fn(String::from("trim"), native_fn(|s: String| s.trim().to_string()), pos.clone())?;
+    context.create_native_fn(String::from("replace"), native_fn(|s: String, from: String, to: String| s.replace(&from, &to)), pos.clone())?;
+    context.create_native_fn(String::from("replace"), native_fn(|s: String, from: char, to: char| s.replace(from, &to.to_string())), pos.clone())?;
+    // the char index of the first occurrence or `()`
+    context.create_native_fn(String::from("find"), native_fn(|s: String, sub: String| match s.find(&sub) {
+        Some(offset) => Value::Int(s[..offset].chars().count() as i64),
+        None => Value::None
+    }), pos.clone())?;
+    context.create_native_fn(String::from("find"), native_fn(|s: String, c: char| match s.chars().position(|other| other == c) {
+        Some(idx) => Value::Int(idx as i64),
+        None => Value::None
+    }), pos.clone())?;
+    context.create_native_fn(String::from("starts-with?"), native_fn(|s: String, prefix: String| s.starts_with(&prefix)), pos.clone())?;
+    context.create_native_fn(String::from("ends-with?"), native_fn(|s: String, suffix: String| s.ends_with(&suffix)), pos.clone())?;
+    context.create_native_fn(String::from("upper"), native_fn(|s: String| s.to_uppercase()), pos.clone())?;
+    context.create_native_fn(String::from("lower"), native_fn(|s: String| s.to_lowercase()), pos.clone())?;
+    context.create_native_fn(String::from("chars"), native_fn(|s: String| s.chars().collect::<Vec<char>>()), pos.clone())?;
+    context.create_native_fn(String::from("lines"), native_fn(|s: String| s.lines().map(String::from).collect::<Vec<String>>()), pos.clone())?;
+    context.create_native_fn(String::from("pad-left"), native_fn(|s: String, width: i64| pad(s, width, ' ', true)), pos.clone())?;
+    context.create_native_fn(String::from("pad-left"), native_fn(|s: String, width: i64, fill: char| pad(s, width, fill, true)), pos.clone())?;
+    context.create_native_fn(String::from("pad-right"), native_fn(|s: String, width: i64| pad(s, width, ' ', false)), pos.clone())?;
+    context.create_native_fn(String::from("pad-right"), native_fn(|s: String, width: i64, fill: char| pad(s, width, fill, false)), pos.clone())?;
+    context.create_native_fn(String::from("repeat"), native_fn(|s: String, n: i64| s.repeat(max(n, 0) as usize)), pos.clone())?;
+    context.create_native_fn(String::from("repeat"), native_fn(|c: char, n: i64| c.to_string().repeat(max(n, 0) as usize)), pos.clone())?;
+    // iter
+    context.create_native_fn(String::from("range"), native_fn(|start: i64, end: i64| Iter::Range(start, end, 1)), pos.clone())?;
+    context.create_native_fn(String::from("range"), NativFunction {
+        params: vec![(":start".to_string(), Type::Int, false), (":end".to_string(), Type::Int, false), (":step".to_string(), Type::Int, false)],
+        return_type: Some(Type::Iterator),
+        body: Rc::new(_range_step),
+        inline: false
+    }, pos.clone())?;
+    context.create_native_fn(String::from("iter"), native_fn(|iter: Iter| iter), pos.clone())?;
+    context.create_native_fn(String::from("collect"), NativFunction {
+        params: vec![(":iter".to_string(), iterable(), false)],
+        return_type: Some(Type::Vector(None)),
+        body: Rc::new(_collect),
+        inline: false
+    }, pos.clone())?;
+    context.create_native_fn(String::from("map"), native_fn(|func: Function, iter: Iter| Iter::Map(Box::new(iter), func)), pos.clone())?;
+    context.create_native_fn(String::from("filter"), native_fn(|func: Function, iter: Iter| Iter::Filter(Box::new(iter), func)), pos.clone())?;
+    context.create_native_fn(String::from("take"), native_fn(|n: i64, iter: Iter| Iter::Take(Box::new(iter), max(n, 0) as usize)), pos.clone())?;
+    context.create_native_fn(String::from("zip"), native_fn(|iter1: Iter, iter2: Iter| Iter::Zip(Box::new(iter1), Box::new(iter2))), pos.clone())?;
+    context.create_native_fn(String::from("enumerate"), native_fn(|iter: Iter| Iter::Enumerate(Box::new(iter), 0)), pos.clone())?;
     // type
-    context.create_native_fn(String::from("type"), NativFunction {
-        params: vec![("v".to_string(), Type::Any, false)],
-        return_type: Some(Type::Type),
-        body: _type,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("type"), native_fn(|v: Value| v.typ()), pos.clone())?;
     // io
-    context.create_native_fn(String::from("write"), NativFunction {
-        params: vec![("v".to_string(), Type::Any, true)],
-        return_type: None,
-        body: _write,
-        inline: false
-    }, pos.clone())?;
-    context.create_native_fn(String::from("print"), NativFunction {
-        params: vec![("v".to_string(), Type::Any, true)],
-        return_type: None,
-        body: _print,
-        inline: false
-    }, pos.clone())?;
+    context.create_native_fn(String::from("write"), native_fn(|v: Rest<Value>| for v in v.0 { print!("{v}") }), pos.clone())?;
+    context.create_native_fn(String::from("print"), native_fn(|v: Rest<Value>| for v in v.0 { println!("{v}") }), pos.clone())?;
     context.create_native_fn(String::from("input"), NativFunction {
         params: vec![("msg".to_string(), Type::String, false)],
         return_type: Some(Type::String),
-        body: _input,
+        body: Rc::new(_input),
         inline: false
     }, pos.clone())?;
     context.create_native_fn(String::from("error!"), NativFunction {
         params: vec![("msg".to_string(), Type::String, false)],
         return_type: None,
-        body: _error,
+        body: Rc::new(_error),
+        inline: false
+    }, pos.clone())?;
+    context.create_native_fn(String::from("error!"), NativFunction {
+        params: vec![("msg".to_string(), Type::String, false), ("code".to_string(), Type::Int, false)],
+        return_type: None,
+        body: Rc::new(_error_code),
         inline: false
     }, pos.clone())?;
     // fs
     context.create_native_fn(String::from("import"), NativFunction {
         params: vec![("path".to_string(), Type::String, false)],
         return_type: None,
-        body: _import,
+        body: Rc::new(_import),
+        inline: false
+    }, pos.clone())?;
+    context.create_native_fn(String::from("import"), NativFunction {
+        params: vec![("path".to_string(), Type::String, false), (":id".to_string(), Type::Key, false)],
+        return_type: None,
+        body: Rc::new(_import_module),
+        inline: true
+    }, pos.clone())?;
+    context.create_native_fn(String::from("import-var"), NativFunction {
+        params: vec![("path".to_string(), Type::String, false), (":ids".to_string(), Type::Key, true)],
+        return_type: None,
+        body: Rc::new(_import_var),
+        inline: true
+    }, pos.clone())?;
+    context.create_native_fn(String::from("export"), NativFunction {
+        params: vec![(":ids".to_string(), Type::Key, true)],
+        return_type: None,
+        body: Rc::new(_export),
         inline: false
     }, pos.clone())?;
     context.create_native_fn(String::from("read-file"), NativFunction {
         params: vec![("path".to_string(), Type::String, false)],
         return_type: Some(Type::String),
-        body: _read_file,
+        body: Rc::new(_read_file),
         inline: false
     }, pos.clone())?;
-    match std_dir_path {
-        Some(std_dir_path) => {
-            let std_path = std_dir_path.clone() + "\\std.nd";
-            run_file_context(&std_path, &mut context)?;
-        },
-        None => {}
+    // env
+    context.create_native_fn(String::from("env"), native_fn(|key: String| env::var(key).map_or(Value::None, Value::String)), pos.clone())?;
+    context.create_native_fn(String::from("env-set"), NativFunction {
+        params: vec![("key".to_string(), Type::String, false), ("value".to_string(), Type::String, false)],
+        return_type: None,
+        body: Rc::new(_env_set),
+        inline: false
+    }, pos.clone())?;
+    context.create_native_fn(String::from("env-vars"), NativFunction {
+        params: vec![],
+        return_type: Some(Type::Object),
+        body: Rc::new(_env_vars),
+        inline: false
+    }, pos.clone())?;
+    context.create_native_fn(String::from("exit"), NativFunction {
+        params: vec![("code".to_string(), Type::Int, false)],
+        return_type: None,
+        body: Rc::new(_exit),
+        inline: false
+    }, pos.clone())?;
+    if let Some(std_dir_path) = std_dir_path {
+        let std_path = std::path::Path::new(&std_dir_path).join(STD_FILE).display().to_string();
+        run_file_context(&std_path, &mut context)?;
     }
     context.scopes = vec![Scope::new()];
+    context.prelude = context.global.clone();
     Ok(context)
-}
\ No newline at end of file
+}
+pub fn std_context_embedded(path: String) -> Result<Context, Error> {
+    let mut context = std_context(path, None)?;
+    run_context(&format!("<STD>/{STD_FILE}"), STD_EMBEDDED.to_string(), &mut context)?;
+    context.scopes = vec![Scope::new()];
+    context.prelude = context.global.clone();
+    Ok(context)
+}
+// the std directory given by the `--std` flag, the `NODY_STD` env var or the one next to the executable
+pub fn find_std_dir(std_flag: Option<String>) -> Option<String> {
+    if let Some(dir) = std_flag { return Some(dir) }
+    if let Ok(dir) = env::var("NODY_STD") { return Some(dir) }
+    let dir = env::current_exe().ok()?.parent()?.join("nody_std");
+    if dir.join(STD_FILE).is_file() { Some(dir.display().to_string()) } else { None }
+}
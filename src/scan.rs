@@ -12,7 +12,7 @@ pub enum Node {
     Word { v: String, pos: Position }, Key { v: String, pos: Position },
     Node { head: NodeRef, args: Vec<NodeRef>, pos: Position }, Body { nodes: Vec<Node>, pos: Position },
     Vector { nodes: Vec<Node>, pos: Position },
-    Closure { node: NodeRef, pos: Position }, Params { params: Vec<(String, NodeRef, bool)>, pos: Position },
+    Closure { node: Rc<Node>, pos: Position }, Params { params: Vec<(String, NodeRef, bool)>, pos: Position },
     Object { entries: Vec<(String, NodeRef)>, pos: Position }
 }
 impl Node {
@@ -35,6 +35,21 @@ impl Node {
             Node::Object { entries:_, pos }         => pos,
         }
     }
+    // every word and key in the node, the names a closure of it can refer to
+    pub fn names<'a>(&'a self, names: &mut Vec<&'a String>) {
+        match self {
+            Node::Word { v, pos:_ } | Node::Key { v, pos:_ } => names.push(v),
+            Node::Node { head, args, pos:_ } => {
+                head.names(names);
+                for arg in args.iter() { arg.names(names); }
+            }
+            Node::Body { nodes, pos:_ } | Node::Vector { nodes, pos:_ } => for node in nodes.iter() { node.names(names); }
+            Node::Closure { node, pos:_ } => node.names(names),
+            Node::Params { params, pos:_ } => for (_, typ, _) in params.iter() { typ.names(names); }
+            Node::Object { entries, pos:_ } => for (_, value) in entries.iter() { value.names(names); }
+            _ => {}
+        }
+    }
 }
 impl Debug for Node {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -69,26 +84,56 @@ impl Display for Node {
     }
 }
 
+pub fn word_to_type(word: &str) -> Option<Type> { parse_type(word, &|_| None) }
+// `named` gives the types declared by the script, like the structs and enums in `Point|none` or `vec<Expr>`
+pub fn parse_type(word: &str, named: &dyn Fn(&str) -> Option<Type>) -> Option<Type> {
+    // `int|float` is a union if every part is a type, `|` inside `<>` belongs to the element type
+    let mut parts = vec![];
+    let (mut depth, mut start) = (0, 0);
+    for (idx, c) in word.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            '|' if depth == 0 => { parts.push(&word[start..idx]); start = idx + 1; }
+            _ => {}
+        }
+    }
+    if !parts.is_empty() {
+        parts.push(&word[start..]);
+        return parts.into_iter().map(|part| parse_type(part, named)).collect::<Option<Vec<Type>>>().map(Type::Union)
+    }
+    if let Some(typ) = word.strip_prefix("vec<").and_then(|word| word.strip_suffix('>')) {
+        return parse_type(typ, named).map(|typ| Type::Vector(Some(Box::new(typ))))
+    }
+    match word {
+        "any"       => Some(Type::Any),
+        "int"       => Some(Type::Int),
+        "float"     => Some(Type::Float),
+        "char"      => Some(Type::Char),
+        "bool"      => Some(Type::Bool),
+        "str"       => Some(Type::String),
+        "key"       => Some(Type::Key),
+        "path"      => Some(Type::Path),
+        "index"     => Some(Type::Index),
+        "closure"   => Some(Type::Closure),
+        "vec"       => Some(Type::Vector(None)),
+        "obj"       => Some(Type::Object),
+        "fn"        => Some(Type::Function(None, None)),
+        "native-fn" => Some(Type::NativFunction(None, None)),
+        "iter"      => Some(Type::Iterator),
+        "type"      => Some(Type::Type),
+        "none"      => Some(Type::None),
+        _ => named(word)
+    }
+}
 pub fn word_to_node(word: String, pos: Position) -> Node {
     match word.as_str() {
         "true"      => Node::Bool { v: true, pos },
         "false"     => Node::Bool { v: false, pos },
-        "any"       => Node::Type { v: Type::Any, pos },
-        "int"       => Node::Type { v: Type::Int, pos },
-        "float"     => Node::Type { v: Type::Float, pos },
-        "char"      => Node::Type { v: Type::Char, pos },
-        "bool"      => Node::Type { v: Type::Bool, pos },
-        "str"       => Node::Type { v: Type::String, pos },
-        "key"       => Node::Type { v: Type::Key, pos },
-        "path"      => Node::Type { v: Type::Path, pos },
-        "index"     => Node::Type { v: Type::Index, pos },
-        "closure"   => Node::Type { v: Type::Closure, pos },
-        "vec"       => Node::Type { v: Type::Vector(None), pos },
-        "obj"       => Node::Type { v: Type::Object, pos },
-        "fn"        => Node::Type { v: Type::Function(vec![], None), pos },
-        "native-fn" => Node::Type { v: Type::NativFunction(vec![], None), pos },
-        "type"      => Node::Type { v: Type::Type, pos },
-        _ => Node::Word { v: word, pos }
+        _ => match word_to_type(&word) {
+            Some(v) => Node::Type { v, pos },
+            None => Node::Word { v: word, pos }
+        }
     }
 }
 
@@ -101,14 +146,22 @@ impl Scanner {
         Self { idx: 0, ln: 0, col: 0, text, path: path.clone() }
     }
     pub fn get(&self) -> char {
-        self.text.get(self.idx..self.idx+1).or_else(|| Some("")).unwrap().chars().next().or_else(|| Some('\0')).unwrap()
+        self.text.get(self.idx..).and_then(|rest| rest.chars().next()).unwrap_or('\0')
+    }
+    pub fn pos(&self) -> Position {
+        Position::new(self.ln..self.ln+1, self.col..self.col+1, &self.path)
+    }
+    pub fn pos_from(&self, start_ln: usize, start_col: usize) -> Position {
+        Position::new(start_ln..self.ln+1, start_col..self.col, &self.path)
     }
     pub fn advance(&mut self) {
-        self.idx += 1; self.col += 1;
         if self.get() == '\n' {
             self.ln += 1;
             self.col = 0;
+        } else {
+            self.col += 1;
         }
+        self.idx += self.get().len_utf8(); // the index is a byte offset
     }
     pub fn advance_ws(&mut self) {
         while WS.contains(&self.get()) || self.get() == ';' {
@@ -137,7 +190,7 @@ impl Scanner {
         if self.get() == '\0' { return Ok(None) }
         self.advance_ws();
         match self.get() {
-            ')' | ']' | '}' => Err(Error::UnexpectedSymbol(self.get())),
+            ')' | ']' | '}' => Err(Error::UnexpectedSymbol(self.get(), self.pos())),
             '(' => {
                 let (start_ln, start_col) = (self.ln, self.col);
                 self.advance(); self.advance_ws();
@@ -148,16 +201,16 @@ impl Scanner {
                     return Ok(Some(Node::None { pos: Position::new(start_ln..stop_ln, start_col..stop_col, &self.path) }))
                 }
                 let head = self.node()?; self.advance_ws();
-                if head.is_none() { return Err(Error::UnexpectedEnd) }
+                if head.is_none() { return Err(Error::UnexpectedEnd(self.pos())) }
                 let head = Box::new(head.unwrap());
                 let mut args: Vec<Box<Node>> = vec![];
                 while self.get() != ')' && self.get() != '\0' {
                     let arg = self.node()?; self.advance_ws();
-                    if arg.is_none() { return Err(Error::UnexpectedEnd) }
+                    if arg.is_none() { return Err(Error::UnexpectedEnd(self.pos())) }
                     let arg = Box::new(arg.unwrap());
                     args.push(arg);
                 }
-                if self.get() == '\0' { return Err(Error::UnexpectedEnd) }
+                if self.get() == '\0' { return Err(Error::UnexpectedEnd(self.pos())) }
                 self.advance();
                 Ok(Some(Node::Node { head, args, pos: Position::new(start_ln..self.ln+1, start_col..self.col, &self.path) }))
             }
@@ -169,7 +222,7 @@ impl Scanner {
                     let node = self.node()?; self.advance_ws();
                     if let Some(node) = node { nodes.push(node); }
                 }
-                if self.get() == '\0' { return Err(Error::UnexpectedEnd) }
+                if self.get() == '\0' { return Err(Error::UnexpectedEnd(self.pos())) }
                 self.advance();
                 if nodes.len() == 1 {
                     Ok(Some(nodes[0].clone()))
@@ -183,11 +236,11 @@ impl Scanner {
                 let mut nodes: Vec<Node> = vec![];
                 while self.get() != ']' && self.get() != '\0' {
                     let node = self.node()?; self.advance_ws();
-                    if node.is_none() { return Err(Error::UnexpectedEnd) }
+                    if node.is_none() { return Err(Error::UnexpectedEnd(self.pos())) }
                     let node = node.unwrap();
                     nodes.push(node);
                 }
-                if self.get() == '\0' { return Err(Error::UnexpectedEnd) }
+                if self.get() == '\0' { return Err(Error::UnexpectedEnd(self.pos())) }
                 self.advance();
                 Ok(Some(Node::Vector { nodes, pos: Position::new(start_ln..self.ln+1, start_col..self.col, &self.path) }))
             }
@@ -199,15 +252,15 @@ impl Scanner {
                     word.push(self.get());
                     self.advance();
                 }
-                if self.get() == '\0' { return Err(Error::UnexpectedEnd) }
+                if self.get() == '\0' { return Err(Error::UnexpectedEnd(self.pos())) }
                 Ok(Some(Node::Key { v: word, pos: Position::new(start_ln..self.ln+1, start_col..self.col, &self.path) }))
             }
             '#' => {
                 let (start_ln, start_col) = (self.ln, self.col);
                 self.advance(); self.advance_ws();
                 let node = self.node()?;
-                if node.is_none() { return Err(Error::UnexpectedEnd) }
-                let node = Box::new(node.unwrap());
+                if node.is_none() { return Err(Error::UnexpectedEnd(self.pos())) }
+                let node = Rc::new(node.unwrap());
                 Ok(Some(Node::Closure { node, pos: Position::new(start_ln..self.ln+1, start_col..self.col, &self.path) }))
             }
             '$' => {
@@ -223,13 +276,13 @@ impl Scanner {
                                 param.push(self.get());
                                 self.advance();
                             }
-                            if param.len() == 0 { return Err(Error::ExpectedWord) }
+                            if param.is_empty() { return Err(Error::ExpectedWord(self.pos())) }
                             self.advance_ws();
                             if SYMBOLS.contains(&self.get()) {
                                 let typ = self.node()?;
-                                if typ.is_none() { return Err(Error::UnexpectedEnd) }
+                                if typ.is_none() { return Err(Error::UnexpectedEnd(self.pos())) }
                                 let typ = Box::new(typ.unwrap());
-                                let mut more = self.get() == '*';
+                                let more = self.get() == '*';
                                 if more { self.advance(); self.advance_ws(); }
                                 params.push((param, typ, more));
                             } else {
@@ -239,15 +292,15 @@ impl Scanner {
                                     self.advance();
                                 }
                                 let pos = Position::new(start_ln..self.ln+1, start_col..self.col, &self.path);
-                                if typ.len() == 0 { return Err(Error::ExpectedWord) }
+                                if typ.is_empty() { return Err(Error::ExpectedWord(self.pos())) }
                                 self.advance_ws();
                                 let typ = Box::new(word_to_node(typ, pos));
-                                let mut more = self.get() == '*';
+                                let more = self.get() == '*';
                                 if more { self.advance(); self.advance_ws(); }
                                 params.push((param, typ, more));
                             }
                         }
-                        if self.get() == '\0' { return Err(Error::UnexpectedEnd) }
+                        if self.get() == '\0' { return Err(Error::UnexpectedEnd(self.pos())) }
                         self.advance();
                         Ok(Some(Node::Params { params, pos: Position::new(start_ln..self.ln+1, start_col..self.col, &self.path) }))
                     }
@@ -260,18 +313,18 @@ impl Scanner {
                                 key.push(self.get());
                                 self.advance();
                             }
-                            if key.len() == 0 { return Err(Error::ExpectedWord) }
+                            if key.is_empty() { return Err(Error::ExpectedWord(self.pos())) }
                             self.advance_ws();
                             let value = self.node()?; self.advance_ws();
-                            if value.is_none() { return Err(Error::UnexpectedEnd) }
+                            if value.is_none() { return Err(Error::UnexpectedEnd(self.pos())) }
                             let value = Box::new(value.unwrap());
                             entries.push((key, value));
                         }
-                        if self.get() == '\0' { return Err(Error::UnexpectedEnd) }
+                        if self.get() == '\0' { return Err(Error::UnexpectedEnd(self.pos())) }
                         self.advance();
                         Ok(Some(Node::Object { entries, pos: Position::new(start_ln..self.ln+1, start_col..self.col, &self.path) }))
                     }
-                    _ => Err(Error::ExpectedSymbols(vec!['(', '{'], self.get()))
+                    _ => Err(Error::ExpectedSymbols(vec!['(', '{'], self.get(), self.pos()))
                 }
             }
             '"' => {
@@ -293,11 +346,11 @@ impl Scanner {
                         self.advance();
                     }
                 }
-                if self.get() == '\0' { return Err(Error::UnclosedString) }
+                if self.get() == '\0' { return Err(Error::UnclosedString(self.pos_from(start_ln, start_col))) }
                 self.advance();
                 match string.parse::<String>() {
                     Ok(string) => Ok(Some(Node::String { v: string, pos: Position::new(start_ln..self.ln+1, start_col..self.col, &self.path) })),
-                    Err(_) => Err(Error::ParseString(string))
+                    Err(_) => Err(Error::ParseString(string, self.pos_from(start_ln, start_col)))
                 }
             }
             '\'' => {
@@ -319,11 +372,11 @@ impl Scanner {
                         self.advance();
                     }
                 }
-                if self.get() == '\0' { return Err(Error::UnclosedChar) }
+                if self.get() == '\0' { return Err(Error::UnclosedChar(self.pos_from(start_ln, start_col))) }
                 self.advance();
                 match c.parse::<char>() {
                     Ok(c) => Ok(Some(Node::Char { v: c, pos: Position::new(start_ln..self.ln+1, start_col..self.col, &self.path) })),
-                    Err(_) => Err(Error::ParseChar(c))
+                    Err(_) => Err(Error::ParseChar(c, self.pos_from(start_ln, start_col)))
                 }
             }
             // numbers
@@ -343,15 +396,15 @@ impl Scanner {
                     }
                     match number.parse() {
                         Ok(number) => Ok(Some(Node::Float { v: number, pos: Position::new(start_ln..self.ln+1, start_col..self.col, &self.path) })),
-                        Err(_) => Err(Error::ParseFloat(number))
+                        Err(_) => Err(Error::ParseFloat(number, self.pos_from(start_ln, start_col)))
                     }
                 } else {
                     match number.parse() {
                         Ok(number) => Ok(Some(Node::Int { v: number, pos: Position::new(start_ln..self.ln+1, start_col..self.col, &self.path) })),
                         Err(e) => match e.kind() {
-                            IntErrorKind::PosOverflow => Err(Error::ParseIntOverflow(number)),
-                            IntErrorKind::NegOverflow => Err(Error::ParseIntNegOverflow(number)),
-                            _ => Err(Error::ParseInt(number)),
+                            IntErrorKind::PosOverflow => Err(Error::ParseIntOverflow(number, self.pos_from(start_ln, start_col))),
+                            IntErrorKind::NegOverflow => Err(Error::ParseIntNegOverflow(number, self.pos_from(start_ln, start_col))),
+                            _ => Err(Error::ParseInt(number, self.pos_from(start_ln, start_col))),
                         }
                     }
                 }
@@ -1,20 +1,88 @@
 use crate::*;
 
 pub type Params = Vec<(String, Type, bool)>;
+// same param types, where `any` only equals `any`
+pub fn params_exact(params1: &Params, params2: &Params) -> bool {
+    params1.len() == params2.len() && params1.iter().zip(params2.iter())
+    .all(|((_, typ1, more1), (_, typ2, more2))| exact_type(typ1, typ2) && more1 == more2)
+}
+// how specific the params are for the argument types of a call, then how many arguments fixed params take
+pub fn params_rank(params: &Params, pattern: &Vec<Type>) -> (usize, usize) {
+    let (mut score, mut fixed, mut idx) = (0, 0, 0);
+    for (_, typ, more) in params.iter() {
+        if *more {
+            while let Some(arg) = pattern.get(idx) {
+                if !arg.matches(typ) { break }
+                score += typ.specificity();
+                idx += 1;
+            }
+        } else {
+            score += typ.specificity();
+            fixed += 1;
+            idx += 1;
+        }
+    }
+    (score, fixed)
+}
+// the variables and functions a closure refers to, captured from the frame it was created in
+#[derive(Debug, Clone)]
+pub struct Env {
+    pub scope: Rc<RefCell<Scope>>,
+    pub frame: usize // id of the frame that created it
+}
+impl Env {
+    pub fn new(scope: Scope, frame: usize) -> Self { Self { scope: Rc::new(RefCell::new(scope)), frame } }
+}
+#[derive(Clone)]
+pub struct Closure {
+    pub node: Rc<Node>,
+    pub env: Option<Env>
+}
+impl Closure {
+    pub fn new(node: Rc<Node>, env: Option<Env>) -> Self { Self { node, env } }
+}
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool { self.node == other.node }
+}
 #[derive(Clone)]
 pub struct Function {
     pub params: Params,
     pub return_type: Option<Type>,
-    pub body: NodeRef,
-    pub inline: bool
+    pub body: Rc<Node>,
+    pub inline: bool,
+    pub env: Option<Env>,
+    pub generator: bool // if it yields
 }
 impl Function {
-    pub fn new(params: Params, return_type: Option<Type>, body: NodeRef, inline: bool) -> Self {
-        Self { params, return_type, body, inline }
+    pub fn new(params: Params, return_type: Option<Type>, body: Rc<Node>, inline: bool) -> Self {
+        let generator = !inline && yields(&body);
+        Self { params, return_type, body, inline, env: None, generator }
+    }
+    // inline functions run in the scope of their caller and don't need an environment
+    pub fn with_env(mut self, env: Option<Env>) -> Self {
+        if self.inline { return self }
+        self.env = env.map(|env| {
+            let mut scope = env.scope.borrow().clone();
+            for (param, _, _) in self.params.iter() {
+                scope.vars.remove(param);
+                scope.cells.remove(param);
+            }
+            Env::new(scope, env.frame)
+        });
+        self
+    }
+    // a local function that calls itself finds itself in its environment, it didn't exist yet when that was captured
+    pub fn with_self(mut self, id: &String, pos: &Position, frame: usize) -> Self {
+        let mut names: Vec<&String> = vec![];
+        self.body.names(&mut names);
+        if self.inline || !names.contains(&id) { return self }
+        let env = self.env.get_or_insert_with(|| Env::new(Scope::new(), frame)).clone();
+        env.scope.borrow_mut().funcs.entry(id.clone()).or_default().push((self.clone(), pos.clone()));
+        self
     }
     pub fn type_params(&self) -> Vec<Type> {
         let mut types: Vec<Type> = vec![];
-        for (_, typ, more) in self.params.iter() { types.push(typ.clone()); }
+        for (_, typ, _more) in self.params.iter() { types.push(typ.clone()); }
         types
     }
     pub fn get_pattern(&self) -> Vec<(Type, bool)> {
@@ -23,48 +91,39 @@ impl Function {
         types
     }
     pub fn return_type_boxed(&self) -> Option<Box<Type>> {
-        if let Some(t) = &self.return_type { Some(Box::new(t.clone())) } else { None }
+        self.return_type.as_ref().map(|t| Box::new(t.clone()))
     }
     pub fn pattern_match(&self, pattern: &Vec<Type>) -> bool {
         let mut pattern_idx: usize = 0;
         for i in 0..self.params.len() {
-            if pattern.get(pattern_idx) == None { return false }
+            if pattern.get(pattern_idx).is_none() { return false }
             let (_, param_type, more) = &self.params[i];
             if *more {
                 if let Some(typ) = pattern.get(pattern_idx) { // one of the param_typ type has to be here
-                    if typ != param_type { return false }
+                    if !typ.matches(param_type) { return false }
                     pattern_idx += 1;
                 } else { return false }
                 while let Some(typ) = pattern.get(pattern_idx) { // skip through the rest
-                    if typ != param_type { break }
+                    if !typ.matches(param_type) { break }
                     pattern_idx += 1;
                 }
             } else {
-                if &pattern[pattern_idx] != param_type { return false }
+                if !pattern[pattern_idx].matches(param_type) { return false }
                 pattern_idx += 1;
             }
         }
-        pattern.get(pattern_idx) == None
-    }
-    pub fn params_match(&self, params: &Params) -> bool {
-        if self.params.len() != params.len() { return false }
-        for i in 0..self.params.len() {
-            if params.get(i) == None { return false }
-            let (_, param_type1, more1) = &self.params[i];
-            let (_, param_type2, more2) = &params[i];
-            if param_type1 != param_type2 || more1 != more2 { return false }
-        }
-        true
+        pattern.get(pattern_idx).is_none()
     }
+    pub fn params_match(&self, params: &Params) -> bool { params_exact(&self.params, params) }
 }
 impl Debug for Function {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "fn({})", self.params.iter().map(|(_, typ, more)| typ.to_string()).collect::<Vec<String>>().join(" "))
+        write!(f, "fn({})", self.params.iter().map(|(_, typ, _more)| typ.to_string()).collect::<Vec<String>>().join(" "))
     }
 }
 impl Display for Function {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "fn({})", self.params.iter().map(|(_, typ, more)| typ.to_string()).collect::<Vec<String>>().join(" "))
+        write!(f, "fn({})", self.params.iter().map(|(_, typ, _more)| typ.to_string()).collect::<Vec<String>>().join(" "))
     }
 }
 impl PartialEq for Function {
@@ -76,7 +135,21 @@ impl PartialEq for Function {
         true
     }
 }
-pub type NativeFunctionType = fn(&mut Context) -> Result<(Option<Value>, Return), Error>;
+// if a `yield` is in the node, leaving out the bodies of functions defined in it
+pub fn yields(node: &Node) -> bool {
+    match node {
+        Node::Node { head, args, pos:_ } => match head.as_ref() {
+            Node::Word { v, pos:_ } if v == "yield" => true,
+            Node::Word { v, pos:_ } if v == "fn" || v.starts_with("def") => false,
+            head => yields(head) || args.iter().any(|arg| yields(arg))
+        }
+        Node::Body { nodes, pos:_ } | Node::Vector { nodes, pos:_ } => nodes.iter().any(yields),
+        Node::Closure { node, pos:_ } => yields(node),
+        Node::Object { entries, pos:_ } => entries.iter().any(|(_, node)| yields(node)),
+        _ => false
+    }
+}
+pub type NativeFunctionType = Rc<dyn Fn(&mut Context) -> Result<(Option<Value>, Return), Error>>;
 #[derive(Clone)]
 pub struct NativFunction {
     pub params: Params,
@@ -85,8 +158,8 @@ pub struct NativFunction {
     pub inline: bool
 }
 impl NativFunction {
-    pub fn new(params: Params, return_type: Option<Type>, body: NativeFunctionType, inline: bool) -> Self {
-        Self { params, return_type, body, inline }
+    pub fn new(params: Params, return_type: Option<Type>, body: impl Fn(&mut Context) -> Result<(Option<Value>, Return), Error> + 'static, inline: bool) -> Self {
+        Self { params, return_type, body: Rc::new(body), inline }
     }
     pub fn type_params(&self) -> Vec<Type> {
         let mut types: Vec<Type> = vec![];
@@ -99,46 +172,37 @@ impl NativFunction {
         types
     }
     pub fn return_type_boxed(&self) -> Option<Box<Type>> {
-        if let Some(t) = &self.return_type { Some(Box::new(t.clone())) } else { None }
+        self.return_type.as_ref().map(|t| Box::new(t.clone()))
     }
     pub fn pattern_match(&self, pattern: &Vec<Type>) -> bool {
         let mut pattern_idx: usize = 0;
         for i in 0..self.params.len() {
-            if pattern.get(pattern_idx) == None { return false }
+            if pattern.get(pattern_idx).is_none() { return false }
             let (_, param_type, more) = &self.params[i];
             if *more {
-                if &pattern[pattern_idx] != param_type { return false }
+                if !pattern[pattern_idx].matches(param_type) { return false }
                 pattern_idx += 1;
                 while let Some(typ) = pattern.get(pattern_idx) {
-                    if typ != param_type { return false }
+                    if !typ.matches(param_type) { return false }
                     pattern_idx += 1;
                 }
             } else {
-                if &pattern[pattern_idx] != param_type { return false }
+                if !pattern[pattern_idx].matches(param_type) { return false }
                 pattern_idx += 1;
             }
         }
-        pattern.get(pattern_idx) == None
-    }
-    pub fn params_match(&self, params: &Params) -> bool {
-        if self.params.len() != params.len() { return false }
-        for i in 0..self.params.len() {
-            if params.get(i) == None { return false }
-            let (_, param_type1, more1) = &self.params[i];
-            let (_, param_type2, more2) = &params[i];
-            if param_type1 != param_type2 || more1 != more2 { return false }
-        }
-        true
+        pattern.get(pattern_idx).is_none()
     }
+    pub fn params_match(&self, params: &Params) -> bool { params_exact(&self.params, params) }
 }
 impl Debug for NativFunction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "native-fn({})", self.params.iter().map(|(_, typ, more)| typ.to_string()).collect::<Vec<String>>().join(" "))
+        write!(f, "native-fn({})", self.params.iter().map(|(_, typ, _more)| typ.to_string()).collect::<Vec<String>>().join(" "))
     }
 }
 impl Display for NativFunction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "native-fn({})", self.params.iter().map(|(_, typ, more)| typ.to_string()).collect::<Vec<String>>().join(" "))
+        write!(f, "native-fn({})", self.params.iter().map(|(_, typ, _more)| typ.to_string()).collect::<Vec<String>>().join(" "))
     }
 }
 impl PartialEq for NativFunction {
@@ -177,45 +241,47 @@ pub struct Path {
 }
 impl Path {
     pub fn new(head: PathWays, sub: String) -> Self { Self { head, sub } }
-    pub fn get_head<'a>(&'a self, context: &'a mut Context) -> Result<Option<&'a Value>, Error> {
+    pub fn get_head<'a>(&'a self, context: &'a mut Context, pos: &Position) -> Result<Option<&'a Value>, Error> {
         match &self.head {
-            PathWays::Key(key) => Ok(context.get_var(&key)),
-            PathWays::Path(path) => path.get(context),
-            PathWays::Index(index) => index.get(context)
+            PathWays::Key(key) => Ok(context.get_var(key)),
+            PathWays::Path(path) => path.get(context, pos),
+            PathWays::Index(index) => index.get(context, pos)
         }
     }
-    pub fn get<'a>(&'a self, context: &'a mut Context) -> Result<Option<&'a Value>, Error> {
+    pub fn get<'a>(&'a self, context: &'a mut Context, pos: &Position) -> Result<Option<&'a Value>, Error> {
         let sub = self.sub.clone();
-        match self.get_head(context)? {
+        match self.get_head(context, pos)? {
             Some(value) => match value {
                 Value::Object(scope) => match scope.get_var(&sub) {
                     Some(value) => Ok(Some(value)),
                     None => Ok(None)
                 }
+                Value::Struct(v) => Ok(v.get(&sub)),
                 _ => {
-                    Err(Error::ExpectedType(Type::Object, value.typ()))
+                    Err(Error::ExpectedType(Type::Object, value.typ(), pos.clone()))
                 }
             }
             _ => Ok(None)
         }
     }
-    pub fn get_head_mut<'a>(&'a mut self, context: &'a mut Context) -> Result<Option<&'a mut Value>, Error> {
+    pub fn get_head_mut<'a>(&'a mut self, context: &'a mut Context, pos: &Position) -> Result<Option<&'a mut Value>, Error> {
         match &mut self.head {
-            PathWays::Key(key) => Ok(context.get_var_mut(&key)),
-            PathWays::Path(path) => path.get_mut(context),
-            PathWays::Index(index) => index.get_mut(context)
+            PathWays::Key(key) => Ok(context.get_var_mut(key)),
+            PathWays::Path(path) => path.get_mut(context, pos),
+            PathWays::Index(index) => index.get_mut(context, pos)
         }
     }
-    pub fn get_mut<'a>(&'a mut self, context: &'a mut Context) -> Result<Option<&'a mut Value>, Error> {
+    pub fn get_mut<'a>(&'a mut self, context: &'a mut Context, pos: &Position) -> Result<Option<&'a mut Value>, Error> {
         let sub = self.sub.clone();
-        match self.get_head_mut(context)? {
+        match self.get_head_mut(context, pos)? {
             Some(value) => match value {
                 Value::Object(scope) => match scope.get_var_mut(&sub) {
                     Some(value) => Ok(Some(value)),
                     None => Ok(None)
                 }
+                Value::Struct(v) => Ok(v.get_mut(&sub)),
                 _ => {
-                    Err(Error::ExpectedType(Type::Object, value.typ()))
+                    Err(Error::ExpectedType(Type::Object, value.typ(), pos.clone()))
                 }
             }
             _ => Ok(None)
@@ -228,6 +294,13 @@ impl Path {
             PathWays::Index(index) => index.is_mutable(context)
         }
     }
+    // the declared type of the field if the head is a struct
+    pub fn field_type(&self, context: &mut Context, pos: &Position) -> Result<Option<Type>, Error> {
+        match self.get_head(context, pos)? {
+            Some(Value::Struct(v)) => Ok(v.field_type(&self.sub).cloned()),
+            _ => Ok(None)
+        }
+    }
 }
 impl Debug for Path {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -246,55 +319,55 @@ pub struct Index {
 }
 impl Index {
     pub fn new(head: PathWays, idx: usize) -> Self { Self { head, idx } }
-    pub fn get_head<'a>(&'a self, context: &'a mut Context) -> Result<Option<&'a Value>, Error> {
+    pub fn get_head<'a>(&'a self, context: &'a mut Context, pos: &Position) -> Result<Option<&'a Value>, Error> {
         match &self.head {
-            PathWays::Key(key) => Ok(context.get_var(&key)),
-            PathWays::Path(path) => path.get(context),
-            PathWays::Index(index) => index.get(context)
+            PathWays::Key(key) => Ok(context.get_var(key)),
+            PathWays::Path(path) => path.get(context, pos),
+            PathWays::Index(index) => index.get(context, pos)
         }
     }
-    pub fn get<'a>(&'a self, context: &'a mut Context) -> Result<Option<&'a Value>, Error> {
+    pub fn get<'a>(&'a self, context: &'a mut Context, pos: &Position) -> Result<Option<&'a Value>, Error> {
         let idx = self.idx;
-        match self.get_head(context)? {
+        match self.get_head(context, pos)? {
             Some(value) =>  match value {
                 Value::Vector(values, _) => {
                     let len = values.len();
                     match values.get(idx) {
                         Some(value) => Ok(Some(value)),
                         None => {
-                            Err(Error::IndexOutOfRange(idx, len))
+                            Err(Error::IndexOutOfRange(idx, len, pos.clone()))
                         }
                     }
                 }
                 _ => {
-                    Err(Error::ExpectedType(Type::Vector(None), value.typ()))
+                    Err(Error::ExpectedType(Type::Vector(None), value.typ(), pos.clone()))
                 }
             }
             _ => Ok(None)
         }
     }
-    pub fn get_head_mut<'a>(&'a mut self, context: &'a mut Context) -> Result<Option<&'a mut Value>, Error> {
+    pub fn get_head_mut<'a>(&'a mut self, context: &'a mut Context, pos: &Position) -> Result<Option<&'a mut Value>, Error> {
         match &mut self.head {
-            PathWays::Key(key) => Ok(context.get_var_mut(&key)),
-            PathWays::Path(path) => path.get_mut(context),
-            PathWays::Index(index) => index.get_mut(context)
+            PathWays::Key(key) => Ok(context.get_var_mut(key)),
+            PathWays::Path(path) => path.get_mut(context, pos),
+            PathWays::Index(index) => index.get_mut(context, pos)
         }
     }
-    pub fn get_mut<'a>(&'a mut self, context: &'a mut Context) -> Result<Option<&'a mut Value>, Error> {
+    pub fn get_mut<'a>(&'a mut self, context: &'a mut Context, pos: &Position) -> Result<Option<&'a mut Value>, Error> {
         let idx = self.idx;
-        match self.get_head_mut(context)? {
+        match self.get_head_mut(context, pos)? {
             Some(value) =>  match value {
                 Value::Vector(values, _) => {
                     let len = values.len();
                     match values.get_mut(idx) {
                         Some(value) => Ok(Some(value)),
                         None => {
-                            Err(Error::IndexOutOfRange(idx, len))
+                            Err(Error::IndexOutOfRange(idx, len, pos.clone()))
                         }
                     }
                 }
                 _ => {
-                    Err(Error::ExpectedType(Type::Vector(None), value.typ()))
+                    Err(Error::ExpectedType(Type::Vector(None), value.typ(), pos.clone()))
                 }
             }
             _ => Ok(None)
@@ -318,18 +391,50 @@ impl Display for Index {
         write!(f, "{}[{}]", self.head, self.idx)
     }
 }
+// a value of a type declared with `struct` or a variant of an `enum`, the values are in the order of the fields
+#[derive(Clone, PartialEq)]
+pub struct Struct {
+    pub name: String,
+    pub enum_name: Option<String>,
+    pub fields: Rc<Params>,
+    pub values: Vec<Value>
+}
+impl Struct {
+    pub fn new(name: String, enum_name: Option<String>, fields: Rc<Params>, values: Vec<Value>) -> Self { Self { name, enum_name, fields, values } }
+    pub fn typ(&self) -> Type {
+        match &self.enum_name {
+            Some(enum_name) => Type::Variant(enum_name.clone(), self.name.clone()),
+            None => Type::Struct(self.name.clone())
+        }
+    }
+    fn field_idx(&self, id: &String) -> Option<usize> { self.fields.iter().position(|(field, _, _)| field == id) }
+    pub fn get(&self, id: &String) -> Option<&Value> { self.values.get(self.field_idx(id)?) }
+    pub fn get_mut(&mut self, id: &String) -> Option<&mut Value> {
+        let idx = self.field_idx(id)?;
+        self.values.get_mut(idx)
+    }
+    pub fn field_type(&self, id: &String) -> Option<&Type> { self.fields.get(self.field_idx(id)?).map(|(_, typ, _)| typ) }
+}
+impl Display for Struct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.fields.is_empty() { return write!(f, "{}", self.name) }
+        write!(f, "{} {{ {} }}", self.name, self.fields.iter().zip(self.values.iter()).map(|((field, _, _), value)| format!("{field}={value:?}"))
+        .collect::<Vec<String>>().join(" "))
+    }
+}
 #[derive(Clone, PartialEq)]
 pub enum Value {
-    Int(i64), Float(f64), Char(char), Bool(bool),
+    None, Int(i64), Float(f64), Char(char), Bool(bool),
     String(String), Vector(Vec<Value>, Option<Type>),
     Key(String), Path(Path), Index(Index),
-    Closure(Node), Params(Params),
-    Function(Function), NativFunction(NativFunction), Object(Scope),
-    Type(Type)
+    Closure(Closure), Params(Params),
+    Function(Function), NativFunction(NativFunction), Object(Scope), Struct(Struct),
+    Iterator(Iter), Type(Type)
 }
 impl Value {
     pub fn typ(&self) -> Type {
         match self {
+            Self::None             => Type::None,
             Self::Int(_)           => Type::Int,
             Self::Float(_)         => Type::Float,
             Self::Char(_)          => Type::Char,
@@ -341,9 +446,11 @@ impl Value {
             Self::Index(_)         => Type::Index,
             Self::Closure(_)       => Type::Closure,
             Self::Params(_)        => Type::Params,
-            Self::Function(f)      => Type::Function(f.type_params(), f.return_type_boxed()),
-            Self::NativFunction(f) => Type::NativFunction(f.type_params(), f.return_type_boxed()),
+            Self::Function(f)      => Type::Function(Some(f.type_params()), f.return_type_boxed()),
+            Self::NativFunction(f) => Type::NativFunction(Some(f.type_params()), f.return_type_boxed()),
             Self::Object(_)        => Type::Object,
+            Self::Struct(v)        => v.typ(),
+            Self::Iterator(_)      => Type::Iterator,
             Self::Type(_)          => Type::Type
         }
     }
@@ -351,6 +458,7 @@ impl Value {
 impl Debug for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match self {
+            Self::None             => "()".to_string(),
             Self::Int(v)           => v.to_string(),
             Self::Float(v)         => v.to_string(),
             Self::Char(v)          => format!("'{v}'"),
@@ -360,7 +468,7 @@ impl Debug for Value {
             Self::Key(v)           => format!("@{v}"),
             Self::Path(v)          => format!("{v}"),
             Self::Index(v)         => format!("{v}"),
-            Self::Closure(n)       => format!("#{n:?}"),
+            Self::Closure(c)       => format!("#{:?}", c.node),
             Self::Params(params)   => format!("$({})",
             params.iter().map(|(id, typ, more)|
                 format!("{} {}{}", id, typ, if *more { "*" } else { "" }))
@@ -369,6 +477,8 @@ impl Debug for Value {
             Self::NativFunction(v) => v.to_string(),
             Self::Object(scope)    => format!("{{ {} }}", scope.vars.iter().map(|(key, (value, _, _))|format!("{key}={value:?}"))
             .collect::<Vec<String>>().join(" ")),
+            Self::Struct(v)        => v.to_string(),
+            Self::Iterator(_)      => "iter".to_string(),
             Self::Type(v)          => v.to_string()
         })
     }
@@ -376,6 +486,7 @@ impl Debug for Value {
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match self {
+            Self::None             => "()".to_string(),
             Self::Int(v)           => v.to_string(),
             Self::Float(v)         => v.to_string(),
             Self::Char(v)          => v.to_string(),
@@ -385,7 +496,7 @@ impl Display for Value {
             Self::Key(v)           => format!("@{v}"),
             Self::Path(v)          => format!("{v:?}"),
             Self::Index(v)         => format!("{v:?}"),
-            Self::Closure(n)       => format!("#{n}"),
+            Self::Closure(c)       => format!("#{}", c.node),
             Self::Params(params)   => format!("$({})",
             params.iter().map(|(id, typ, more)|
                 format!("{} {}{}", id, typ, if *more { "*" } else { "" }))
@@ -394,39 +505,62 @@ impl Display for Value {
             Self::NativFunction(v) => v.to_string(),
             Self::Object(scope)    => format!("{{ {} }}", scope.vars.iter().map(|(key, (value, _, _))| format!("{key}={value:?}"))
             .collect::<Vec<String>>().join(" ")),
+            Self::Struct(v)        => v.to_string(),
+            Self::Iterator(_)      => "iter".to_string(),
             Self::Type(v)          => v.to_string()
         })
     }
 }
 #[derive(Clone)]
 pub enum Type {
-    Any,
+    Any, None,
     Int, Float, Char, Bool,
     String, Vector(Option<Box<Type>>),
     Key, Path, Index,
     Closure, Params,
-    Function(Vec<Type>, Option<Box<Type>>), NativFunction(Vec<Type>, Option<Box<Type>>), Object,
-    Type
+    Function(Option<Vec<Type>>, Option<Box<Type>>), NativFunction(Option<Vec<Type>>, Option<Box<Type>>), Object, Struct(String), Enum(String), Variant(String, String),
+    Iterator, Type,
+    Union(Vec<Type>)
+}
+impl Type {
+    // exact types are more specific than unions of them, than `vec<T>` and than `any`
+    pub fn specificity(&self) -> usize {
+        match self {
+            Self::Any => 0,
+            Self::Vector(t) => 2 + t.as_ref().map(|t| t.specificity()).unwrap_or(0),
+            Self::Function(p, _) | Self::NativFunction(p, _) => if p.is_some() { 4 } else { 2 },
+            Self::Object => 3, // also matches every struct
+            Self::Enum(_) => 3, // also matches every variant
+            Self::Union(t) => t.iter().map(|t| t.specificity()).min().unwrap_or(0).saturating_sub(1),
+            _ => 4
+        }
+    }
 }
 impl Debug for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match self {
             Self::Any                 => "any".to_string(),
+            Self::None                => "none".to_string(),
             Self::Int                 => "int".to_string(),
             Self::Float               => "float".to_string(),
             Self::Char                => "char".to_string(),
             Self::Bool                => "bool".to_string(),
             Self::String              => "str".to_string(),
-            Self::Vector(t)           => if let Some(t) = t { format!("vec<{t:?}>") } else { format!("vec") }
+            Self::Vector(t)           => if let Some(t) = t { format!("vec<{t:?}>") } else { "vec".to_string() }
             Self::Key                 => "key".to_string(),
             Self::Path                => "path".to_string(),
             Self::Index               => "index".to_string(),
             Self::Closure             => "closure".to_string(),
             Self::Params              => "params".to_string(),
-            Self::Function(p, r)      => format!("fn({p:?})"),
-            Self::NativFunction(p, r) => format!("nativ-fn({p:?})"),
+            Self::Function(p, _r)      => if let Some(p) = p { format!("fn({p:?})") } else { "fn".to_string() }
+            Self::NativFunction(p, _r) => if let Some(p) = p { format!("nativ-fn({p:?})") } else { "nativ-fn".to_string() }
             Self::Object              => "obj".to_string(),
-            Self::Type                => "type".to_string()
+            Self::Struct(name)        => name.clone(),
+            Self::Enum(name)          => name.clone(),
+            Self::Variant(_, name)    => name.clone(),
+            Self::Iterator            => "iter".to_string(),
+            Self::Type                => "type".to_string(),
+            Self::Union(t)            => t.iter().map(|x| format!("{x:?}")).collect::<Vec<String>>().join("|")
         })
     }
 }
@@ -434,28 +568,56 @@ impl Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match self {
             Self::Any                 => "any".to_string(),
+            Self::None                => "none".to_string(),
             Self::Int                 => "int".to_string(),
             Self::Float               => "float".to_string(),
             Self::Char                => "char".to_string(),
             Self::Bool                => "bool".to_string(),
             Self::String              => "str".to_string(),
-            Self::Vector(t)           => if let Some(t) = t { format!("vec<{t}>") } else { format!("vec") }
+            Self::Vector(t)           => if let Some(t) = t { format!("vec<{t}>") } else { "vec".to_string() }
             Self::Key                 => "key".to_string(),
             Self::Path                => "path".to_string(),
             Self::Index               => "index".to_string(),
             Self::Closure             => "closure".to_string(),
             Self::Params              => "params".to_string(),
-            Self::Function(t, _)      => format!("fn({})", t.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" ")),
-            Self::NativFunction(t, _) => format!("nativ-fn({})", t.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" ")),
+            Self::Function(t, _)      => if let Some(t) = t {
+                format!("fn({})", t.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" "))
+            } else { "fn".to_string() }
+            Self::NativFunction(t, _) => if let Some(t) = t {
+                format!("nativ-fn({})", t.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" "))
+            } else { "nativ-fn".to_string() }
             Self::Object              => "obj".to_string(),
-            Self::Type                => "type".to_string()
+            Self::Struct(name)        => name.clone(),
+            Self::Enum(name)          => name.clone(),
+            Self::Variant(_, name)    => name.clone(),
+            Self::Iterator            => "iter".to_string(),
+            Self::Type                => "type".to_string(),
+            Self::Union(t)            => t.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("|")
         })
     }
 }
+impl Type {
+    // a value of this type can be passed where `expected` is declared, a struct is an object and a variant is of its enum
+    pub fn matches(&self, expected: &Type) -> bool {
+        match (self, expected) {
+            (Self::Struct(_) | Self::Variant(_, _), Self::Object) => true,
+            (Self::Variant(e1, _), Self::Enum(e2)) => e1 == e2,
+            (Self::Union(t1), Self::Union(t2)) => t1.iter().all(|t1| t2.iter().any(|t2| t1.matches(t2))),
+            (t1, Self::Union(t2)) => t2.iter().any(|t2| t1.matches(t2)),
+            (Self::Vector(Some(t1)), Self::Vector(Some(t2))) if !matches!(t1.as_ref(), Type::Any) => t1.matches(t2),
+            _ => self == expected
+        }
+    }
+}
 impl PartialEq for Type {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Any, _) => true, (_, Self::Any) => true,
+            // a union matches every type of its own, and unions whose types it all has
+            (Self::Union(t1), Self::Union(t2)) => t1.iter().all(|t1| t2.contains(t1)),
+            (Self::Union(t1), t2) => t1.iter().any(|t1| t1 == t2),
+            (t1, Self::Union(t2)) => t2.iter().any(|t2| t1 == t2),
+            (Self::None, Self::None)        => true,
             (Self::Int, Self::Int)          => true,
             (Self::Float, Self::Float)      => true,
             (Self::Char, Self::Char)        => true,
@@ -464,10 +626,7 @@ impl PartialEq for Type {
             (Self::Vector(t1), Self::Vector(t2)) => match t1 {
                 Some(t1) => match t1.as_ref() {
                     Type::Any => match t2 {
-                        Some(t2) => match t2.as_ref() {
-                            Type::Any => true,
-                            _ => false
-                        }
+                        Some(t2) => matches!(t2.as_ref(), Type::Any),
                         None => false
                     }
                     _ => match t2 {
@@ -482,9 +641,20 @@ impl PartialEq for Type {
             (Self::Index, Self::Index)      => true,
             (Self::Closure, Self::Closure)  => true,
             (Self::Params, Self::Params)    => true,
-            (Self::Function(p1, t1), Self::Function(p2, t2)) => p1 == p2 && t1 == t2,
-            (Self::NativFunction(p1, t1), Self::NativFunction(p2, t2)) => p1 == p2 && t1 == t2,
+            // a plain `fn` matches every function
+            (Self::Function(p1, t1), Self::Function(p2, t2)) => match (p1, p2) {
+                (Some(p1), Some(p2)) => p1 == p2 && t1 == t2,
+                _ => true
+            }
+            (Self::NativFunction(p1, t1), Self::NativFunction(p2, t2)) => match (p1, p2) {
+                (Some(p1), Some(p2)) => p1 == p2 && t1 == t2,
+                _ => true
+            }
             (Self::Object, Self::Object)    => true,
+            (Self::Struct(n1), Self::Struct(n2)) => n1 == n2,
+            (Self::Enum(n1), Self::Enum(n2)) => n1 == n2,
+            (Self::Variant(e1, n1), Self::Variant(e2, n2)) => e1 == e2 && n1 == n2,
+            (Self::Iterator, Self::Iterator) => true,
             (Self::Type, Self::Type)        => true,
             _ => false
         }
@@ -0,0 +1,190 @@
+use crate::*;
+
+#[derive(Debug, Clone)]
+pub struct Vm {
+    pub chunks: HashMap<*const Node, (Rc<Node>, Rc<Chunk>)> // the node is kept so its address can't be reused
+}
+impl Default for Vm { fn default() -> Self { Self::new() } }
+impl Vm {
+    pub fn new() -> Self { Self { chunks: HashMap::new() } }
+    pub fn chunk(&mut self, node: &Rc<Node>) -> Rc<Chunk> {
+        self.chunks.entry(Rc::as_ptr(node))
+        .or_insert_with(|| (node.clone(), Rc::new(compile(node))))
+        .1.clone()
+    }
+}
+
+// strict version of `Type::eq` for the call caches, where `any` must not match everything
+pub fn exact_type(t1: &Type, t2: &Type) -> bool {
+    match (t1, t2) {
+        (Type::Any, Type::Any) | (Type::None, Type::None) | (Type::Int, Type::Int) | (Type::Float, Type::Float) | (Type::Char, Type::Char) |
+        (Type::Bool, Type::Bool) | (Type::String, Type::String) | (Type::Key, Type::Key) | (Type::Path, Type::Path) |
+        (Type::Index, Type::Index) | (Type::Closure, Type::Closure) | (Type::Params, Type::Params) |
+        (Type::Object, Type::Object) | (Type::Iterator, Type::Iterator) | (Type::Type, Type::Type) => true,
+        (Type::Vector(t1), Type::Vector(t2)) => match (t1, t2) {
+            (Some(t1), Some(t2)) => exact_type(t1, t2),
+            (None, None) => true,
+            _ => false
+        }
+        (Type::Union(t1), Type::Union(t2)) => exact_types(t1, t2),
+        (Type::Struct(n1), Type::Struct(n2)) | (Type::Enum(n1), Type::Enum(n2)) => n1 == n2,
+        (Type::Variant(e1, n1), Type::Variant(e2, n2)) => e1 == e2 && n1 == n2,
+        (Type::Function(p1, r1), Type::Function(p2, r2)) | (Type::NativFunction(p1, r1), Type::NativFunction(p2, r2)) => {
+            let params = match (p1, p2) {
+                (Some(p1), Some(p2)) => exact_types(p1, p2),
+                (None, None) => true,
+                _ => false
+            };
+            params && match (r1, r2) {
+                (Some(r1), Some(r2)) => exact_type(r1, r2),
+                (None, None) => true,
+                _ => false
+            }
+        }
+        _ => false
+    }
+}
+pub fn exact_types(types1: &[Type], types2: &[Type]) -> bool {
+    types1.len() == types2.len() && types1.iter().zip(types2.iter()).all(|(t1, t2)| exact_type(t1, t2))
+}
+
+fn args(stack: &mut Vec<(Option<Value>, Return)>, len: usize) -> Vec<Value> {
+    stack.drain(stack.len() - len..).map(|(value, _)| value.unwrap()).collect()
+}
+pub fn execute(chunk: &Chunk, context: &mut Context) -> Result<(Option<Value>, Return), Error> {
+    let mut stack: Vec<(Option<Value>, Return)> = vec![];
+    let mut pc: usize = 0;
+    while let Some(instr) = chunk.code.get(pc) {
+        pc += 1;
+        match instr {
+            Instr::None => stack.push((None, Return::None)),
+            Instr::Value(value) => stack.push((Some(value.clone()), Return::None)),
+            Instr::Closure(node) => stack.push((Some(Value::Closure(Closure::new(node.clone(), context.capture(node)))), Return::None)),
+            Instr::Word(id, pos) => match context.get_var(id) {
+                Some(value) => stack.push((Some(value.clone()), Return::None)),
+                None => if let Some(typ) = context.get_type(id) { stack.push((Some(Value::Type(typ)), Return::None)) } else {
+                    context.trace_push(pos);
+                    return Err(Error::NotDefined(id.clone(), pos.clone()))
+                }
+            }
+            Instr::Element(idx, pos) => {
+                let value = match &stack.last().unwrap().0 {
+                    Some(value) => value,
+                    None => {
+                        context.trace_push(pos);
+                        return Err(Error::Expected(pos.clone()))
+                    }
+                };
+                if *idx > 0 {
+                    let typ = stack[stack.len() - 1 - idx].0.as_ref().unwrap().typ();
+                    if typ != value.typ() {
+                        let value_typ = value.typ();
+                        context.trace_push(pos);
+                        return Err(Error::ExpectedType(typ, value_typ, pos.clone()))
+                    }
+                }
+            }
+            Instr::Vector(len) => {
+                let values = args(&mut stack, *len);
+                let typ = values.first().map(|value| value.typ());
+                stack.push((Some(Value::Vector(values, typ)), Return::None));
+            }
+            Instr::ParamType(type_pos, pos) => match &stack.last().unwrap().0 {
+                Some(Value::Type(_)) => {}
+                Some(value) => {
+                    let typ = value.typ();
+                    context.trace_push(type_pos);
+                    return Err(Error::ExpectedType(Type::Type, typ, type_pos.clone()))
+                }
+                None => {
+                    context.trace_push(pos);
+                    return Err(Error::Expected(pos.clone()))
+                }
+            }
+            Instr::Params(names) => {
+                let types = args(&mut stack, names.len());
+                let mut params: Params = vec![];
+                for ((param, more), typ) in names.iter().zip(types) {
+                    if let Value::Type(typ) = typ { params.push((param.clone(), typ, *more)); }
+                }
+                stack.push((Some(Value::Params(params)), Return::None));
+            }
+            Instr::Entry(pos) => if stack.last().unwrap().0.is_none() {
+                context.trace_push(pos);
+                return Err(Error::Expected(pos.clone()))
+            }
+            Instr::Object(entries) => {
+                let values = args(&mut stack, entries.len());
+                let mut obj_scope = Scope::new();
+                for ((key, pos), value) in entries.iter().zip(values) {
+                    obj_scope.create_var(key.clone(), value, true, pos.clone(), true)?;
+                }
+                stack.push((Some(Value::Object(obj_scope)), Return::None));
+            }
+            Instr::Push => context.push(),
+            Instr::Pop => { context.pop(); }
+            Instr::Stmt(end) => {
+                let (value, ret) = stack.pop().unwrap();
+                if ret != Return::None {
+                    context.pop();
+                    stack.push((value, ret));
+                    pc = *end;
+                }
+            }
+            Instr::Arg(pos) => if stack.last().unwrap().0.is_none() {
+                context.trace_push(pos);
+                return Err(Error::ExpectedArg(pos.clone()))
+            }
+            Instr::CallWord { id, word_pos, pos, poses, cache } => {
+                let values = args(&mut stack, poses.len());
+                let types: Vec<Type> = values.iter().map(|value| value.typ()).collect();
+                // only definitions of the global scope are independent of the frame calling
+                let local = id.contains('.') || context.frame().iter().any(|scope| scope.fn_exists(id) || scope.native_fn_exists(id));
+                let cached = match cache.borrow().as_ref() {
+                    Some((version, cache_types, callee)) if !local && *version == context.version && exact_types(cache_types, &types) => Some(callee.clone()),
+                    _ => None
+                };
+                let res = match cached {
+                    Some(callee) => {
+                        let res = match callee.as_ref() {
+                            Callee::Native(func) => call_native_fn(func, values, poses, context),
+                            Callee::Fn(func) => call_fn(func, values, poses, context)
+                        };
+                        match res {
+                            Ok(res) => {
+                                context.pop();
+                                res
+                            }
+                            Err(e) => {
+                                context.trace_push(pos);
+                                return Err(e)
+                            }
+                        }
+                    }
+                    None => {
+                        if !local {
+                            if let Ok(Some(callee)) = context.resolve_fn(id, &types, word_pos) {
+                                *cache.borrow_mut() = Some((context.version, types.clone(), Rc::new(callee)));
+                            }
+                        }
+                        call_word(id, word_pos, pos, values, types, poses, context)?
+                    }
+                };
+                stack.push(res);
+            }
+            Instr::CallValue { head_pos, pos, poses } => {
+                let head_value = stack.pop().unwrap().0;
+                let values = args(&mut stack, poses.len());
+                let types: Vec<Type> = values.iter().map(|value| value.typ()).collect();
+                match head_value {
+                    Some(head_value) => stack.push(call_value(head_value, head_pos, pos, values, types, poses, context)?),
+                    None => {
+                        context.trace_push(head_pos);
+                        return Err(Error::Expected(head_pos.clone()))
+                    }
+                }
+            }
+        }
+    }
+    Ok(stack.pop().unwrap_or((None, Return::None)))
+}
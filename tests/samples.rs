@@ -0,0 +1,24 @@
+use std::process::{Command, Output, Stdio};
+use std::fs;
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_nody")).args(args).stdin(Stdio::null()).output().unwrap()
+}
+
+// every sample prints the same and fails the same way under the interpreter and the vm
+#[test]
+fn samples_vm_matches_interpret() {
+    let mut paths: Vec<String> = fs::read_dir("samples").unwrap()
+        .map(|entry| entry.unwrap().path().display().to_string())
+        .filter(|path| path.ends_with(".nd"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths.iter() {
+        let interpreted = run(&[path]);
+        let executed = run(&["--vm", path]);
+        assert_eq!(String::from_utf8_lossy(&executed.stdout), String::from_utf8_lossy(&interpreted.stdout), "output of {path}");
+        assert_eq!(String::from_utf8_lossy(&executed.stderr), String::from_utf8_lossy(&interpreted.stderr), "errors of {path}");
+        assert_eq!(executed.status.code(), interpreted.status.code(), "exit code of {path}");
+    }
+}